@@ -1,18 +1,65 @@
-use std::str::FromStr;
+use std::{fmt, path::PathBuf, str::FromStr, time::Duration};
 
 use clap::builder::TypedValueParser as _;
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 use reqwest::Url;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    gate::QualityGate,
+    package::ByteSize,
+    report::ReportTarget,
+    severity::{SeverityFilter, SeverityMap},
+};
+
+pub(crate) const TEMPLATES: [&str; 3] = ["default", "high", "user_defined"];
+pub(crate) const FORMATS: [&str; 8] = [
+    "json",
+    "sarif",
+    "html",
+    "junit",
+    "gitlab-codequality",
+    "gitlab-sast",
+    "markdown",
+    "cyclonedx",
+];
+pub(crate) const LOG_LEVELS: [&str; 5] = ["off", "debug", "info", "warn", "error"];
+
 /// Codepecker 的命令行程序
+///
+/// 参数的优先级从高到低为: 命令行参数, CODEPECKER_* 环境变量, 当前目录下的 codepecker.toml,
+/// 用户目录下的 .codepecker.toml.
+/// 互斥的参数(eg. --file、--dir、--git 与 --svn, --password 与 --token)只使用优先级最高的来源中的值.
 #[derive(Parser, Debug, Clone)]
 #[command(author,version, about, long_about = None)]
 pub(crate) struct Codepecker {
+    #[command(subcommand)]
+    pub(crate) command: Command,
+
+    #[command(flatten)]
+    pub(crate) global: GlobalArgs,
+}
+
+/// 所有子命令共用的参数
+#[derive(Args, Debug, Clone)]
+pub(crate) struct GlobalArgs {
+    /// 设置配置文件, 指定后不再读取当前目录及用户目录下的配置文件.
+    #[arg(
+        long,
+        value_name = "Config File",
+        global = true,
+        env = "CODEPECKER_CONFIG"
+    )]
+    pub(crate) config: Option<PathBuf>,
+
     /// 设置 Codepecker 的访问地址. eg. http://pecker.abc.local:8081.
     #[arg(
         short,
         long,
         value_name = "Url",
-        default_value = "http://127.0.0.1:8081"
+        global = true,
+        default_value = "http://127.0.0.1:8081",
+        env = "CODEPECKER_URL"
     )]
     pub(crate) url: Option<Url>,
     /// 设置 Codepecker 的apikey. eg. adfadfe343g.
@@ -20,78 +67,659 @@ pub(crate) struct Codepecker {
         short,
         long,
         value_name = "Apikey",
-        default_value = "Oh9LHLfrLgk77e67DEZtiitOWZwvFVXI"
+        global = true,
+        env = "CODEPECKER_KEY",
+        hide_env_values = true
     )]
     pub(crate) key: Option<String>,
+    /// 从文件读取 Codepecker 的apikey, 为 - 时从标准输入读取.
+    #[arg(
+        long,
+        value_name = "Apikey File",
+        global = true,
+        env = "CODEPECKER_KEY_FILE"
+    )]
+    pub(crate) key_file: Option<PathBuf>,
 
     /// 设置 Codepecker 的连接代理. eg. http://127.0.0.1:8080.
-    #[arg(long, value_name = "Proxy")]
+    #[arg(long, value_name = "Proxy", global = true, env = "CODEPECKER_PROXY")]
     pub(crate) proxy: Option<Url>,
+    /// 设置请求遇到连接失败、超时、5xx或429等暂时性错误时的最大重试次数, 为0时不重试.
+    #[arg(
+        long,
+        value_name = "Retries",
+        global = true,
+        default_value = "3",
+        env = "CODEPECKER_RETRIES"
+    )]
+    pub(crate) retries: Option<u32>,
+    /// 设置重试的初始间隔, 之后每次翻倍. eg. 1s.
+    #[arg(
+        long,
+        value_name = "Retry Backoff",
+        global = true,
+        default_value = "1s",
+        env = "CODEPECKER_RETRY_BACKOFF"
+    )]
+    pub(crate) retry_backoff: Option<HumanDuration>,
+
+    /// 设置输出日志的级别(选择off不输出日志)
+    #[arg(
+        long,
+        global = true,
+        default_value = "debug",
+        value_parser = clap::builder::PossibleValuesParser::new(LOG_LEVELS)
+            .map(|s| log::LevelFilter::from_str(&s).unwrap()),
+        env = "CODEPECKER_LOG_LEVEL",
+    )]
+    pub(crate) log_level: log::LevelFilter,
+}
 
+/// 下发检测任务的项目参数
+#[derive(Args, Debug, Clone)]
+pub(crate) struct ProjectArgs {
     /// 设置 Codepecker 的项目名称.
-    #[arg(short, long, value_name = "Project Name", default_value = "test")]
+    #[arg(
+        short,
+        long,
+        value_name = "Project Name",
+        default_value = "test",
+        env = "CODEPECKER_PROJECT"
+    )]
     pub(crate) project: Option<String>,
     /// 设置 Codepecker 的项目组id.
-    #[arg(long, value_name = "Project Group ID")]
+    #[arg(long, value_name = "Project Group ID", env = "CODEPECKER_GROUP")]
     pub(crate) group: Option<String>,
     /// 设置 Codepecker 的项目语言.
-    #[arg(short, long, value_name = "Project Language", default_value = "java")]
+    #[arg(
+        short,
+        long,
+        value_name = "Project Language",
+        default_value = "java",
+        env = "CODEPECKER_LANG"
+    )]
     pub(crate) lang: Option<String>,
     /// 设置 Codepecker 的缺陷模板类型.
-    #[arg(short, long, value_name = "Scan Template",default_value = "default",value_parser = clap::builder::PossibleValuesParser::new(["default", "high", "user_defined"]))]
+    #[arg(short, long, value_name = "Scan Template",default_value = "default",value_parser = clap::builder::PossibleValuesParser::new(TEMPLATES), env = "CODEPECKER_TEMPLATE")]
     pub(crate) template: Option<String>,
     /// 设置 Codepecker 的缺陷模板规则,当缺陷模板(template)类型为 user_defined 时生效.
-    #[arg(short, long, value_name = "Scan Rule")]
+    #[arg(short, long, value_name = "Scan Rule", env = "CODEPECKER_RULE")]
     pub(crate) rule: Option<String>,
+}
 
+/// 源码参数: 本地压缩包、本地目录或由服务端下载的SVN/GIT地址
+#[derive(Args, Debug, Clone)]
+pub(crate) struct SourceArgs {
     /// 设置 Codepecker 的源码文件.
-    #[arg(short, long, value_name = "Zip File")]
+    #[arg(short, long, value_name = "Zip File", env = "CODEPECKER_FILE")]
     pub(crate) file: Option<String>,
 
+    /// 设置 Codepecker 的源码目录, 上传前自动打包为zip(遵循.gitignore, 跳过.git、.svn目录及顶层或构建根目录下的target、node_modules目录).
+    #[arg(long, value_name = "Source Dir", env = "CODEPECKER_DIR")]
+    pub(crate) dir: Option<PathBuf>,
+    /// 设置打包源码目录时包含的文件, 可多次指定. eg. "src/**".
+    #[arg(
+        long,
+        value_name = "Include Glob",
+        env = "CODEPECKER_INCLUDE",
+        value_delimiter = ','
+    )]
+    pub(crate) include: Option<Vec<String>>,
+    /// 设置打包源码目录时排除的文件, 可多次指定. eg. "**/*.min.js".
+    #[arg(
+        long,
+        value_name = "Exclude Glob",
+        env = "CODEPECKER_EXCLUDE",
+        value_delimiter = ','
+    )]
+    pub(crate) exclude: Option<Vec<String>>,
+    /// 设置打包源码目录时是否只包含git跟踪的文件.
+    #[arg(
+        long,
+        value_name = "Git Tracked",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        default_value = "false",
+        env = "CODEPECKER_GIT_TRACKED"
+    )]
+    pub(crate) git_tracked: Option<bool>,
+    /// 设置上传压缩包的大小上限, 超过时不上传直接报错, 打包源码目录时超过上限立即停止打包. eg. 500MB.
+    #[arg(
+        long,
+        value_name = "Max Archive Size",
+        env = "CODEPECKER_MAX_ARCHIVE_SIZE"
+    )]
+    pub(crate) max_archive_size: Option<ByteSize>,
+
     /// 设置 Codepecker 的SVN地址.
-    #[arg(short, long, value_name = "SVN")]
+    #[arg(short, long, value_name = "SVN", env = "CODEPECKER_SVN")]
     pub(crate) svn: Option<Url>,
-    /// 设置 Codepecker 的GIT地址.
-    #[arg(short, long, value_name = "GIT")]
+    /// 设置 Codepecker 的GIT地址, 服务端通过用户名及密码拉取代码, 只支持 http(s) 地址, 不支持SSH地址及SSH密钥.
+    #[arg(short, long, value_name = "GIT", env = "CODEPECKER_GIT")]
     pub(crate) git: Option<Url>,
 
     /// 设置 Codepecker SVN/GIT的用户名.
-    #[arg(long, value_name = "SVN/GIT UserName")]
+    #[arg(long, value_name = "SVN/GIT UserName", env = "CODEPECKER_USER")]
     pub(crate) user: Option<String>,
     /// 设置 Codepecker SVN/GIT的密码.
-    #[arg(long, value_name = "SVN/GIT Password")]
+    #[arg(
+        long,
+        value_name = "SVN/GIT Password",
+        env = "CODEPECKER_PASSWORD",
+        hide_env_values = true
+    )]
     pub(crate) password: Option<String>,
+    /// 从文件读取 Codepecker SVN/GIT的密码, 为 - 时从标准输入读取.
+    #[arg(
+        long,
+        value_name = "SVN/GIT Password File",
+        env = "CODEPECKER_PASSWORD_FILE"
+    )]
+    pub(crate) password_file: Option<PathBuf>,
+    /// 设置 Codepecker SVN/GIT的访问令牌, 未设置用户名时使用 oauth2.
+    /// 未设置密码及令牌时, 依次从仓库地址、.netrc 及 git credential helper 读取凭据, 均未找到时按公开仓库匿名拉取.
+    #[arg(
+        long,
+        value_name = "SVN/GIT Token",
+        env = "CODEPECKER_TOKEN",
+        hide_env_values = true
+    )]
+    pub(crate) token: Option<String>,
     /// 设置 Codepecker SVN/GIT的分支.
-    #[arg(long, value_name = "SVN/GIT Password")]
+    #[arg(long, value_name = "SVN/GIT Password", env = "CODEPECKER_BRANCH")]
     pub(crate) branch: Option<String>,
+}
+
+/// 等待检测完成时的轮询参数
+#[derive(Args, Debug, Clone)]
+pub(crate) struct PollArgs {
+    /// 设置轮询任务状态的初始间隔, 之后每次翻倍直到最大间隔. eg. 5s.
+    #[arg(
+        long,
+        value_name = "Poll Interval",
+        default_value = "5s",
+        env = "CODEPECKER_POLL_INTERVAL"
+    )]
+    pub(crate) poll_interval: Option<HumanDuration>,
+    /// 设置轮询任务状态的最大间隔. eg. 1m.
+    #[arg(
+        long,
+        value_name = "Max Poll Interval",
+        default_value = "60s",
+        env = "CODEPECKER_MAX_POLL_INTERVAL"
+    )]
+    pub(crate) max_poll_interval: Option<HumanDuration>,
+    /// 设置等待检测完成的总超时时间, 超时后以状态码5退出. eg. 30m.
+    #[arg(long, value_name = "Scan Timeout", env = "CODEPECKER_SCAN_TIMEOUT")]
+    pub(crate) scan_timeout: Option<HumanDuration>,
+}
+
+/// 本地wiki缓存参数
+#[derive(Args, Debug, Clone)]
+pub(crate) struct WikiCacheArgs {
+    /// 设置本地wiki缓存的有效期, 缓存位于 ~/.cache/codepecker/wiki/. eg. 7d.
+    #[arg(
+        long,
+        value_name = "Wiki TTL",
+        default_value = "7d",
+        env = "CODEPECKER_WIKI_TTL"
+    )]
+    pub(crate) wiki_ttl: Option<HumanDuration>,
+    /// 设置是否只使用本地wiki缓存获取解决方案(包括已过期的缓存), 不请求服务端.
+    #[arg(
+        long,
+        value_name = "Offline Wiki",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        default_value = "false",
+        env = "CODEPECKER_OFFLINE_WIKI"
+    )]
+    pub(crate) offline_wiki: Option<bool>,
+    /// 设置获取解决方案及源文件时的最大并发请求数
+    #[arg(
+        long,
+        value_name = "Concurrency",
+        default_value = "8",
+        env = "CODEPECKER_CONCURRENCY"
+    )]
+    pub(crate) concurrency: Option<usize>,
+}
+
+/// Markdown 摘要参数
+#[derive(Args, Debug, Clone)]
+pub(crate) struct SummaryArgs {
+    /// 设置额外追加写入 Markdown 摘要的文件. eg. $GITHUB_STEP_SUMMARY.
+    #[arg(long, value_name = "Summary File", env = "CODEPECKER_SUMMARY_FILE")]
+    pub(crate) summary_file: Option<PathBuf>,
+    /// 设置 Markdown 摘要中列出的缺陷数量
+    #[arg(
+        long,
+        value_name = "Top N",
+        default_value = "10",
+        env = "CODEPECKER_SUMMARY_TOP"
+    )]
+    pub(crate) summary_top: Option<usize>,
+    /// 设置 Markdown 摘要的最大字符数, 超出时折叠各部分并截断缺陷列表
+    #[arg(
+        long,
+        value_name = "Summary Limit",
+        default_value = "65000",
+        env = "CODEPECKER_SUMMARY_LIMIT"
+    )]
+    pub(crate) summary_limit: Option<usize>,
+    /// 设置仓库文件链接的基础地址, 同时设置 --commit-sha 时缺陷位置为文件链接. eg. https://github.com/owner/repo/blob.
+    #[arg(long, value_name = "Repository URL", env = "CODEPECKER_REPO_URL")]
+    pub(crate) repo_url: Option<String>,
+    /// 设置仓库文件链接使用的提交 SHA(或分支、标签), 不设置时缺陷位置不生成链接
+    #[arg(long, value_name = "Commit SHA", env = "CODEPECKER_COMMIT_SHA")]
+    pub(crate) commit_sha: Option<String>,
+}
+
+/// 获取检测结果的参数
+#[derive(Args, Debug, Clone)]
+pub(crate) struct ResultArgs {
+    /// 设置对比的基线结果文件(之前输出的json格式结果), 只报告新增的缺陷. 基线中的缺陷按相同的筛选条件筛选
+    #[arg(long, value_name = "Baseline File", env = "CODEPECKER_BASELINE")]
+    pub(crate) baseline: Option<String>,
+
+    /// 设置对比的基线任务id, 只报告新增的缺陷
+    #[arg(
+        long,
+        value_name = "Baseline Task ID",
+        env = "CODEPECKER_BASELINE_TASK"
+    )]
+    pub(crate) baseline_task: Option<String>,
+
+    /// 设置忽略文件, 默认读取当前目录下的 .codepecker-ignore.toml
+    #[arg(long, value_name = "Ignore File", env = "CODEPECKER_IGNORE_FILE")]
+    pub(crate) ignore_file: Option<PathBuf>,
+
+    /// 设置 Codepecker 的缺陷级别,eg: 选择high表示high级别及以上即high和critical两种缺陷,
+    /// =medium 表示只包括medium, low..=high 表示low到high之间的级别, unknown 表示包括未知级别在内的全部缺陷
+    #[arg(
+        long,
+        value_name = "Scan Severity",
+        default_value = "info",
+        env = "CODEPECKER_SEVERITY"
+    )]
+    pub(crate) severity: Option<SeverityFilter>,
+
+    /// 设置服务端 severityLevel 与级别的对应关系, 没有对应关系的 severityLevel 视为 unknown
+    #[arg(
+        long,
+        value_name = "Severity Map",
+        default_value = "1=critical,2=high,3=medium,4=low,5=info",
+        env = "CODEPECKER_SEVERITY_MAP"
+    )]
+    pub(crate) severity_map: Option<SeverityMap>,
+
+    /// 设置只保留的errorCode, 可多次指定
+    #[arg(
+        long,
+        value_name = "Error Code",
+        value_delimiter = ',',
+        env = "CODEPECKER_INCLUDE_ERROR_CODE"
+    )]
+    pub(crate) include_error_code: Option<Vec<String>>,
+
+    /// 设置排除的errorCode, 可多次指定
+    #[arg(
+        long,
+        value_name = "Error Code",
+        value_delimiter = ',',
+        env = "CODEPECKER_EXCLUDE_ERROR_CODE"
+    )]
+    pub(crate) exclude_error_code: Option<Vec<String>>,
+
+    /// 设置只保留的缺陷文件路径, 可多次指定. eg. "src/**".
+    #[arg(
+        long,
+        value_name = "Path Glob",
+        value_delimiter = ',',
+        env = "CODEPECKER_INCLUDE_PATH"
+    )]
+    pub(crate) include_path: Option<Vec<String>>,
+
+    /// 设置排除的缺陷文件路径, 可多次指定. eg. "**/test/**".
+    #[arg(
+        long,
+        value_name = "Path Glob",
+        value_delimiter = ',',
+        env = "CODEPECKER_EXCLUDE_PATH"
+    )]
+    pub(crate) exclude_path: Option<Vec<String>>,
+
+    /// 设置只保留的缺陷类别(服务端返回的 category), 不区分大小写, 可多次指定
+    #[arg(
+        long,
+        value_name = "Category",
+        value_delimiter = ',',
+        env = "CODEPECKER_CATEGORY"
+    )]
+    pub(crate) category: Option<Vec<String>>,
+
+    /// 设置只保留的 CWE 编号, 没有 CWE 编号的缺陷被筛除, 可多次指定. eg. CWE-89,79.
+    #[arg(
+        long,
+        value_name = "CWE",
+        value_delimiter = ',',
+        env = "CODEPECKER_CWE"
+    )]
+    pub(crate) cwe: Option<Vec<String>>,
 
-    /// 设置 Codepecker 的taskid.
-    #[arg(long, value_name = "Task ID")]
-    pub(crate) task: Option<String>,
+    /// 设置 Codepecker 的质量门禁, 各级别缺陷数量超过阈值时以非0状态退出, eg: critical=0,high=5
+    #[arg(long, value_name = "Quality Gate", env = "CODEPECKER_FAIL_ON")]
+    pub(crate) fail_on: Option<QualityGate>,
+
+    /// 设置是否获取开源组件(SCA)检测结果, 输出到结果的 components 中. 输出cyclonedx格式或设置 --fail-on-components 时自动获取
+    #[arg(
+        long,
+        value_name = "Components",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        default_value = "false",
+        env = "CODEPECKER_COMPONENTS"
+    )]
+    pub(crate) components: Option<bool>,
+
+    /// 设置开源组件的级别, 含义与 --severity 相同, 只影响检测结果及质量门禁, cyclonedx 格式的 SBOM 总是包括全部组件.
+    /// 默认为 unknown, 保留全部组件(包括没有级别的组件)
+    #[arg(
+        long,
+        value_name = "Component Severity",
+        default_value = "unknown",
+        env = "CODEPECKER_COMPONENT_SEVERITY"
+    )]
+    pub(crate) component_severity: Option<SeverityFilter>,
 
-    /// 设置 Codepecker 的缺陷级别,eg: 选择high表示high级别及以上即high和critical两种缺陷
-    #[arg(long, value_name = "Scan Severity",default_value = "info",value_parser = clap::builder::PossibleValuesParser::new(["info", "low", "medium", "high", "critical"]))]
-    pub(crate) severity: Option<String>,
+    /// 设置开源组件的质量门禁, 各级别组件数量超过阈值时以非0状态退出, 设置后自动获取开源组件检测结果, eg: critical=0
+    #[arg(
+        long,
+        value_name = "Quality Gate",
+        env = "CODEPECKER_FAIL_ON_COMPONENTS"
+    )]
+    pub(crate) fail_on_components: Option<QualityGate>,
 
     /// 设置 Codepecker 的扫描结果存储位置.
     #[arg(
         short,
         long,
         value_name = "Scan Result",
-        default_value = "results.json"
+        default_value = "results.json",
+        env = "CODEPECKER_OUTPUT"
     )]
     pub(crate) output: Option<String>,
 
-    /// 设置输出日志的级别(选择off不输出日志)
+    /// 设置 Codepecker 的扫描结果格式(sarif 为 SARIF 2.1.0 格式, html 为单文件的 HTML 报告, junit 为 JUnit XML, gitlab-codequality 及 gitlab-sast 为 GitLab 的 Code Quality 及 SAST 报告, markdown 为 Markdown 摘要, cyclonedx 为开源组件的 CycloneDX SBOM).
+    #[arg(long, value_name = "Result Format", default_value = "json", value_parser = clap::builder::PossibleValuesParser::new(FORMATS), env = "CODEPECKER_FORMAT")]
+    pub(crate) format: Option<String>,
+
+    /// 设置同时输出的多个报告, 格式为 格式:路径, 可重复设置. 设置后忽略 --format 及 --output.
+    /// eg. --report json:results.json --report sarif:scan.sarif.
     #[arg(
         long,
-        default_value = "debug",
-        value_parser = clap::builder::PossibleValuesParser::new(["off", "debug", "info", "warn", "error"])
-            .map(|s| log::LevelFilter::from_str(&s).unwrap()),
+        value_name = "Format:Path",
+        action = clap::ArgAction::Append,
+        value_delimiter = ',',
+        env = "CODEPECKER_REPORT"
     )]
-    pub(crate) log_level: log::LevelFilter,
+    pub(crate) report: Option<Vec<ReportTarget>>,
 
     /// 设置是否获取源文件
-    #[arg(long, value_name = "Get Source", default_value = "false")]
+    #[arg(
+        long,
+        value_name = "Get Source",
+        default_value = "false",
+        env = "CODEPECKER_GET_SOURCE"
+    )]
     pub(crate) get_source: Option<bool>,
+
+    /// 获取源文件时, 每个缺陷保留所在行前后各多少行的源码片段
+    #[arg(
+        long,
+        value_name = "Lines",
+        default_value = "3",
+        env = "CODEPECKER_SNIPPET_LINES"
+    )]
+    pub(crate) snippet_lines: Option<u64>,
+
+    #[command(flatten)]
+    pub(crate) wiki: WikiCacheArgs,
+
+    #[command(flatten)]
+    pub(crate) summary: SummaryArgs,
+}
+
+/// 时间长度, 支持 s、m、h、d 单位, 不带单位时为秒. eg. 30s, 10m, 2h, 7d
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HumanDuration(pub(crate) Duration);
+
+impl FromStr for HumanDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: u64 = number
+            .parse()
+            .map_err(|_| format!("时间 {s} 的格式应为 数字+单位, eg. 30s, 10m, 2h, 7d"))?;
+        let unit_seconds: u64 = match unit.trim() {
+            "" | "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86400,
+            other => return Err(format!("未知的时间单位 {other}, 可选值: s,m,h,d")),
+        };
+        let seconds = number
+            .checked_mul(unit_seconds)
+            .ok_or_else(|| format!("时间 {s} 超出范围"))?;
+        Ok(Self(Duration::from_secs(seconds)))
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}s", self.0.as_secs())
+    }
+}
+
+impl Serialize for HumanDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// 配置文件中可以是字符串或秒数, eg. scan_timeout = "30m"
+impl<'de> Deserialize<'de> for HumanDuration {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Seconds(u64),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Seconds(seconds) => Ok(Self(Duration::from_secs(seconds))),
+            Raw::Text(text) => text.parse().map_err(de::Error::custom),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum Command {
+    /// 下发检测任务, 等待检测完成后获取检测结果
+    Scan(Box<ScanArgs>),
+    /// 只下发检测任务, 在标准输出打印任务id
+    Submit(Box<SubmitArgs>),
+    /// 查询检测任务的状态
+    Status(StatusArgs),
+    /// 获取已完成的检测任务的检测结果
+    Results(Box<ResultsArgs>),
+    /// 查看规则wiki或缓存规则wiki
+    Wiki(WikiArgs),
+    /// 从服务端下载源文件
+    File(FileArgs),
+    /// 配置相关的命令
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct ScanArgs {
+    #[command(flatten)]
+    pub(crate) project: ProjectArgs,
+    #[command(flatten)]
+    pub(crate) source: SourceArgs,
+    #[command(flatten)]
+    pub(crate) poll: PollArgs,
+    #[command(flatten)]
+    pub(crate) result: ResultArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct SubmitArgs {
+    #[command(flatten)]
+    pub(crate) project: ProjectArgs,
+    #[command(flatten)]
+    pub(crate) source: SourceArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct StatusArgs {
+    /// 检测任务id
+    pub(crate) task: String,
+    /// 等待检测完成
+    #[arg(long)]
+    pub(crate) wait: bool,
+    #[command(flatten)]
+    pub(crate) poll: PollArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct ResultsArgs {
+    /// 检测任务id
+    pub(crate) task: String,
+    /// 设置检测任务的项目语言, 用于获取解决方案.
+    #[arg(short, long, value_name = "Project Language", env = "CODEPECKER_LANG")]
+    pub(crate) lang: Option<String>,
+    #[command(flatten)]
+    pub(crate) result: ResultArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true)]
+pub(crate) struct WikiArgs {
+    #[command(subcommand)]
+    pub(crate) command: Option<WikiCommand>,
+    /// 查看的errorCode
+    pub(crate) error_code: Option<String>,
+    /// 设置规则的语言.
+    #[arg(short, long, value_name = "Project Language", env = "CODEPECKER_LANG")]
+    pub(crate) lang: Option<String>,
+    #[command(flatten)]
+    pub(crate) cache: WikiCacheArgs,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum WikiCommand {
+    /// 获取规则wiki并写入本地缓存, 供 --offline-wiki 使用
+    ///
+    /// 不指定errorCode及 --from-task 时缓存该语言的全部规则, eg. codepecker wiki prefetch --lang java
+    Prefetch {
+        /// 需要缓存的errorCode, 不指定时缓存全部规则
+        error_codes: Vec<String>,
+        /// 缓存该检测任务所有缺陷的errorCode
+        #[arg(long, value_name = "Task ID")]
+        from_task: Option<String>,
+        /// 设置规则的语言.
+        #[arg(short, long, value_name = "Project Language", env = "CODEPECKER_LANG")]
+        lang: Option<String>,
+        #[command(flatten)]
+        cache: WikiCacheArgs,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct FileArgs {
+    /// 服务端的文件路径(缺陷的filePath)
+    pub(crate) path: String,
+    /// 设置保存的文件, 默认输出到标准输出.
+    #[arg(short, long, value_name = "Output File")]
+    pub(crate) output: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum ConfigCommand {
+    /// 输出子命令生效的参数(合并配置文件、环境变量及命令行参数), apikey、密码及令牌以******代替.
+    /// eg. codepecker config show results <task> --severity high, 不指定子命令时为 scan.
+    Show {
+        #[command(subcommand)]
+        command: Option<ShowCommand>,
+    },
+}
+
+/// config show 可输出的子命令, 参数与对应的子命令相同
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum ShowCommand {
+    Scan(Box<ScanArgs>),
+    Submit(Box<SubmitArgs>),
+    Status(StatusArgs),
+    Results(Box<ResultsArgs>),
+    Wiki(WikiArgs),
+}
+
+impl From<ShowCommand> for Command {
+    fn from(command: ShowCommand) -> Self {
+        match command {
+            ShowCommand::Scan(args) => Command::Scan(args),
+            ShowCommand::Submit(args) => Command::Submit(args),
+            ShowCommand::Status(args) => Command::Status(args),
+            ShowCommand::Results(args) => Command::Results(args),
+            ShowCommand::Wiki(args) => Command::Wiki(args),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(s: &str) -> Result<u64, String> {
+        s.parse::<HumanDuration>()
+            .map(|HumanDuration(d)| d.as_secs())
+    }
+
+    #[test]
+    fn parse_human_duration() {
+        assert_eq!(seconds("30"), Ok(30));
+        assert_eq!(seconds("30s"), Ok(30));
+        assert_eq!(seconds(" 10m "), Ok(600));
+        assert_eq!(seconds("2h"), Ok(7200));
+        assert_eq!(seconds("7d"), Ok(604800));
+        assert_eq!(seconds("0s"), Ok(0));
+        assert_eq!(HumanDuration(Duration::from_secs(600)).to_string(), "600s");
+    }
+
+    #[test]
+    fn reject_invalid_human_duration() {
+        for s in ["", "s", "-1s", "1.5h", "10w", "1 0s"] {
+            assert!(s.parse::<HumanDuration>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn reject_overflowing_human_duration() {
+        assert!(format!("{}d", u64::MAX / 86400 + 1)
+            .parse::<HumanDuration>()
+            .is_err());
+        assert!("99999999999999999999s".parse::<HumanDuration>().is_err());
+        assert_eq!(
+            seconds(&format!("{}d", u64::MAX / 86400)),
+            Ok(u64::MAX / 86400 * 86400)
+        );
+    }
 }
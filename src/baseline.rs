@@ -0,0 +1,182 @@
+use std::{collections::HashMap, fs::File, io::BufReader};
+
+use serde::Deserialize;
+
+use crate::{
+    error::CodepeckerError,
+    model::{BaselineState, Problem},
+};
+
+/// 对比的基线: 之前输出的结果文件或之前的任务id
+#[derive(Debug, Clone)]
+pub(crate) enum Baseline {
+    File(String),
+    Task(String),
+}
+
+// 之前输出的json格式结果文件, 只关心其中的problems
+#[derive(Deserialize)]
+struct BaselineFile {
+    problems: Vec<Problem>,
+}
+
+// 读取之前输出的json格式结果文件
+pub(crate) fn load(path: &str) -> Result<Vec<Problem>, CodepeckerError> {
+    let file = File::open(path)?;
+    let baseline: BaselineFile = serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| CodepeckerError::CustomInvalidInfo(format!("无法解析基线文件{path}: {e}")))?;
+    log::info!("从基线文件{path}读取{}个缺陷", baseline.problems.len());
+    Ok(baseline.problems)
+}
+
+// 按指纹对比本次缺陷与基线, 标记每个缺陷的状态并返回已修复的缺陷.
+// 指纹相同的多个缺陷按数量逐一配对.
+pub(crate) fn diff(problems: &mut [Problem], baseline: Vec<Problem>) -> Vec<Problem> {
+    let mut remaining: HashMap<String, Vec<Problem>> = HashMap::new();
+    for problem in baseline {
+        let key = problem.fingerprint.clone().unwrap_or_default();
+        remaining.entry(key).or_default().push(problem);
+    }
+    for problem in problems.iter_mut() {
+        let matched = problem
+            .fingerprint
+            .as_ref()
+            .and_then(|key| remaining.get_mut(key))
+            .and_then(|same| same.pop())
+            .is_some();
+        problem.baseline_state = Some(if matched {
+            BaselineState::Unchanged
+        } else {
+            BaselineState::New
+        });
+    }
+    let mut fixed: Vec<Problem> = remaining.into_values().flatten().collect();
+    fixed.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+    for problem in &mut fixed {
+        problem.baseline_state = Some(BaselineState::Fixed);
+    }
+    let new = problems
+        .iter()
+        .filter(|p| p.baseline_state == Some(BaselineState::New))
+        .count();
+    log::info!(
+        "与基线对比: 新增{}个, 已修复{}个, 未变化{}个",
+        new,
+        fixed.len(),
+        problems.len() - new
+    );
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::test_problem;
+
+    // code 为缺陷所在行的源码
+    fn problem(error_code: &str, file: &str, line: u64, code: &str) -> Problem {
+        let mut problem = test_problem(json!({
+            "errorCode": error_code,
+            "filePath": file,
+            "line": line
+        }));
+        problem.fingerprint = Some(problem.compute_fingerprint(Some(code)));
+        problem
+    }
+
+    fn states(problems: &[Problem]) -> Vec<BaselineState> {
+        problems.iter().filter_map(|p| p.baseline_state).collect()
+    }
+
+    #[test]
+    fn marks_new_unchanged_and_fixed() {
+        let baseline = vec![
+            problem("NPE", "src/A.java", 4, "s.length();"),
+            problem("LOG", "src/C.java", 2, "log(password);"),
+        ];
+        let mut problems = vec![
+            problem("NPE", "src/A.java", 7, "s.length();"),
+            problem("SQLI", "src/B.java", 10, "query(sql);"),
+        ];
+        let fixed = diff(&mut problems, baseline);
+        assert_eq!(
+            states(&problems),
+            [BaselineState::Unchanged, BaselineState::New]
+        );
+        assert_eq!(fixed.len(), 1);
+        assert_eq!(fixed[0].error_code, "LOG");
+        assert_eq!(fixed[0].baseline_state, Some(BaselineState::Fixed));
+    }
+
+    #[test]
+    fn same_rule_in_same_file_is_told_apart_by_code() {
+        let baseline = vec![
+            problem("LOG", "src/C.java", 2, "log(password);"),
+            problem("LOG", "src/C.java", 3, "log(token);"),
+        ];
+        // 删除第2行后, 原第3行的缺陷仍为未变化, 已删除的缺陷为已修复
+        let mut problems = vec![
+            problem("LOG", "src/C.java", 2, "log(token);"),
+            problem("LOG", "src/C.java", 9, "log(secret);"),
+        ];
+        let fixed = diff(&mut problems, baseline);
+        assert_eq!(
+            states(&problems),
+            [BaselineState::Unchanged, BaselineState::New]
+        );
+        assert_eq!(fixed.len(), 1);
+        assert_eq!(fixed[0].line, Some(2));
+    }
+
+    #[test]
+    fn pairs_same_fingerprint_by_count() {
+        // 完全相同的代码行指纹相同, 按数量逐一配对
+        let baseline = vec![
+            problem("LOG", "src/C.java", 2, "log(password);"),
+            problem("LOG", "src/C.java", 3, "log(password);"),
+        ];
+        let mut problems = vec![
+            problem("LOG", "src/C.java", 2, "log(password);"),
+            problem("LOG", "src/C.java", 3, "log(password);"),
+            problem("LOG", "src/C.java", 9, "log(password);"),
+        ];
+        let fixed = diff(&mut problems, baseline.clone());
+        assert_eq!(
+            states(&problems),
+            [
+                BaselineState::Unchanged,
+                BaselineState::Unchanged,
+                BaselineState::New
+            ]
+        );
+        assert!(fixed.is_empty());
+
+        let mut problems = vec![problem("LOG", "src/C.java", 2, "log(password);")];
+        let fixed = diff(&mut problems, baseline);
+        assert_eq!(states(&problems), [BaselineState::Unchanged]);
+        assert_eq!(fixed.len(), 1);
+    }
+
+    #[test]
+    fn problems_without_fingerprint_are_new() {
+        let mut unfingerprinted = problem("NPE", "src/A.java", 4, "s.length();");
+        unfingerprinted.fingerprint = None;
+        let mut problems = vec![unfingerprinted];
+        let fixed = diff(
+            &mut problems,
+            vec![problem("NPE", "src/A.java", 4, "s.length();")],
+        );
+        assert_eq!(states(&problems), [BaselineState::New]);
+        assert_eq!(fixed.len(), 1);
+    }
+
+    #[test]
+    fn empty_baseline_marks_all_new() {
+        let mut problems = vec![problem("NPE", "src/A.java", 4, "s.length();")];
+        let fixed = diff(&mut problems, Vec::new());
+        assert_eq!(states(&problems), [BaselineState::New]);
+        assert!(fixed.is_empty());
+    }
+}
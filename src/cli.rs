@@ -0,0 +1,579 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+
+use crate::{
+    args::{
+        Codepecker, Command, ConfigCommand, FileArgs, GlobalArgs, HumanDuration, PollArgs,
+        ProjectArgs, ResultArgs, ResultsArgs, ScanArgs, SourceArgs, StatusArgs, SubmitArgs,
+        WikiArgs, WikiCacheArgs, WikiCommand,
+    },
+    baseline::{self, Baseline},
+    config,
+    credential::{self, CredentialOptions},
+    enrich::Enricher,
+    error::CodepeckerError,
+    filter::{self, FilterOptions, ProblemFilter},
+    gate::QualityGate,
+    package::{self, ByteSize, PackageOptions},
+    project::{Project, Source},
+    report::{self, ReportTarget, SummaryOptions, TaskResult},
+    severity::{Severity, SeverityFilter, SeverityMap},
+    suppress::{Suppressions, DEFAULT_IGNORE_FILE},
+    wiki::WikiCache,
+    PeckerClient,
+};
+
+/// 解析命令行参数并执行子命令
+pub async fn builder() -> Result<(), CodepeckerError> {
+    let mut matches = Codepecker::command().get_matches();
+    // config show 未指定子命令时输出 scan 生效的参数
+    if is_bare_config_show(&matches) {
+        matches =
+            Codepecker::command().get_matches_from(env::args_os().chain([OsString::from("scan")]));
+    }
+    let mut args = Codepecker::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // config show <子命令> 按该子命令合并配置
+    let show = match &mut args.command {
+        Command::Config(ConfigCommand::Show { command }) => command.take(),
+        _ => None,
+    };
+    let showing = show.is_some();
+    if let Some(command) = show {
+        args.command = command.into();
+    }
+    let config_files = config::apply(&mut args, &matches)?;
+    if showing {
+        print!("{}", config::show(&args, &config_files));
+        return Ok(());
+    }
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Off)
+        .filter_module("codepecker", args.global.log_level)
+        .init();
+    log::debug!("使用的配置文件: {:?}", config_files);
+
+    match args.command {
+        Command::Scan(scan_args) => scan(&args.global, *scan_args).await,
+        Command::Submit(submit_args) => submit(&args.global, *submit_args).await,
+        Command::Status(status_args) => status(&args.global, status_args).await,
+        Command::Results(results_args) => results(&args.global, *results_args).await,
+        Command::Wiki(wiki_args) => wiki(&args.global, wiki_args).await,
+        Command::File(file_args) => file(&args.global, file_args).await,
+        Command::Config(_) => Ok(()),
+    }
+}
+
+fn is_bare_config_show(matches: &ArgMatches) -> bool {
+    matches!(
+        matches.subcommand(),
+        Some(("config", config)) if matches!(
+            config.subcommand(),
+            Some(("show", show)) if show.subcommand().is_none()
+        )
+    )
+}
+
+// 按全局参数构建客户端, 需要等待检测完成的子命令设置轮询参数
+fn client(
+    global: &GlobalArgs,
+    poll: Option<&PollArgs>,
+    max_archive_size: Option<ByteSize>,
+) -> Result<PeckerClient, CodepeckerError> {
+    let key = global.key.clone().ok_or_else(|| {
+        CodepeckerError::MissingParam(
+            "apikey, 请通过 --key、--key-file、CODEPECKER_KEY 或配置文件设置".to_owned(),
+        )
+    })?;
+    let mut client =
+        PeckerClient::builder(global.url.clone().unwrap(), key).danger_accept_invalid_certs(true);
+    if let Some(proxy) = &global.proxy {
+        client = client.proxy(proxy.clone());
+    }
+    if let Some(retries) = global.retries {
+        client = client.max_retries(retries);
+    }
+    if let Some(HumanDuration(backoff)) = global.retry_backoff {
+        client = client.retry_backoff(backoff);
+    }
+    if let Some(ByteSize(size)) = max_archive_size {
+        client = client.max_upload_size(size);
+    }
+    if let Some(poll) = poll {
+        if let Some(HumanDuration(interval)) = poll.poll_interval {
+            client = client.poll_interval(interval);
+        }
+        if let Some(HumanDuration(interval)) = poll.max_poll_interval {
+            client = client.max_poll_interval(interval);
+        }
+        if let Some(HumanDuration(timeout)) = poll.scan_timeout {
+            client = client.scan_timeout(timeout);
+        }
+    }
+    let pecker = client.build()?;
+    log::debug!("{pecker:?}");
+    Ok(pecker)
+}
+
+fn project(args: &ProjectArgs) -> Result<Project, CodepeckerError> {
+    let template = args.template.clone().unwrap_or_default();
+    if template == "user_defined" && args.rule.is_none() {
+        return Err(CodepeckerError::MissingParam(
+            "rule, 缺陷模板类型为 user_defined 时需要设置缺陷模板规则".to_owned(),
+        ));
+    }
+    let project = Project {
+        name: args.project.clone().unwrap_or_default(),
+        lang: args.lang.clone().unwrap_or_default(),
+        template,
+        rule: args.rule.clone(),
+        group: args.group.clone(),
+    };
+    log::debug!("输入的参数：项目：{:?}", project);
+    Ok(project)
+}
+
+// 按源码参数下发检测任务, 返回任务id
+async fn submit_task(
+    pecker: &PeckerClient,
+    project: &Project,
+    args: &SourceArgs,
+) -> Result<String, CodepeckerError> {
+    let sources = [
+        args.file.is_some(),
+        args.dir.is_some(),
+        args.git.is_some(),
+        args.svn.is_some(),
+    ];
+    if sources.into_iter().filter(|set| *set).count() > 1 {
+        return Err(CodepeckerError::InvalidConfig(
+            "file、dir、git、svn 只能设置其中之一".to_owned(),
+        ));
+    }
+    if let Some(code_file) = &args.file {
+        return pecker.post_source_code(project, code_file).await;
+    }
+    if let Some(dir) = &args.dir {
+        let options = PackageOptions {
+            include: args.include.clone().unwrap_or_default(),
+            exclude: args.exclude.clone().unwrap_or_default(),
+            git_tracked: args.git_tracked.unwrap_or(false),
+            max_size: args.max_archive_size.map(|ByteSize(size)| size),
+        };
+        let archive = package::package(dir, &options)?;
+        return pecker.post_source_code(project, archive.path()).await;
+    }
+    let (remote, mut url) = match (&args.git, &args.svn) {
+        (Some(git), _) => ("2", git.clone()),
+        (None, Some(svn)) => ("1", svn.clone()),
+        (None, None) => {
+            return Err(CodepeckerError::MissingParam(
+                "源码, 请通过 --file、--dir、--git 或 --svn 指定".to_owned(),
+            ))
+        }
+    };
+    let credentials = credential::resolve(
+        &mut url,
+        &CredentialOptions {
+            user: args.user.as_deref(),
+            password: args.password.as_deref(),
+            token: args.token.as_deref(),
+            git: remote == "2",
+        },
+    )?;
+    let (user, password) = credentials
+        .map(|c| (c.user, c.password))
+        .unwrap_or_default();
+    let source = Source {
+        remote: remote.to_owned(),
+        url,
+        user,
+        password,
+        branch: args.branch.clone(),
+    };
+    log::debug!("{source:?}");
+    pecker
+        .post_source_code_by_svn_or_git(project, &source)
+        .await
+}
+
+// 下发检测任务, 等待检测完成后获取检测结果
+async fn scan(global: &GlobalArgs, args: ScanArgs) -> Result<(), CodepeckerError> {
+    let pecker = client(global, Some(&args.poll), args.source.max_archive_size)?;
+    let project = project(&args.project)?;
+    let task = submit_task(&pecker, &project, &args.source).await?;
+    log::info!("代码扫描任务: {:?}下发完成", task);
+    pecker.query_task_status(&task).await?;
+    log::info!("代码扫描任务: {:?}扫描完成", task);
+    fetch_results(&pecker, &task, &project.lang, &args.result).await
+}
+
+// 只下发检测任务, 输出任务id供之后通过 status 及 results 子命令使用
+async fn submit(global: &GlobalArgs, args: SubmitArgs) -> Result<(), CodepeckerError> {
+    let pecker = client(global, None, args.source.max_archive_size)?;
+    let project = project(&args.project)?;
+    let task = submit_task(&pecker, &project, &args.source).await?;
+    log::info!("代码扫描任务: {:?}下发完成", task);
+    println!("{task}");
+    Ok(())
+}
+
+// 查询检测任务的状态, 输出 任务id 及 状态
+async fn status(global: &GlobalArgs, args: StatusArgs) -> Result<(), CodepeckerError> {
+    let pecker = client(global, Some(&args.poll), None)?;
+    if args.wait {
+        pecker.query_task_status(&args.task).await?;
+    }
+    let status = pecker.get_task_status(&args.task).await?;
+    if status.status != 0 {
+        return Err(CodepeckerError::CustomInvalidInfo(
+            status
+                .error_msg
+                .unwrap_or_else(|| "查询任务状态失败".to_owned()),
+        ));
+    }
+    println!("{}\t{status}", args.task);
+    Ok(())
+}
+
+// 获取已完成的检测任务的检测结果
+async fn results(global: &GlobalArgs, args: ResultsArgs) -> Result<(), CodepeckerError> {
+    let language = args.lang.ok_or_else(|| {
+        CodepeckerError::MissingParam(
+            "lang, 请通过 --lang、CODEPECKER_LANG 或配置文件设置检测任务的项目语言".to_owned(),
+        )
+    })?;
+    let pecker = client(global, None, None)?;
+    log::info!("外部传入扫描id{:?}", args.task);
+    fetch_results(&pecker, &args.task, &language, &args.result).await
+}
+
+// 查看规则wiki, 或获取规则wiki并写入本地缓存
+async fn wiki(global: &GlobalArgs, args: WikiArgs) -> Result<(), CodepeckerError> {
+    let missing_lang = || {
+        CodepeckerError::MissingParam(
+            "lang, 请通过 --lang、CODEPECKER_LANG 或配置文件设置规则的语言".to_owned(),
+        )
+    };
+    let pecker = client(global, None, None)?;
+    if let Some(WikiCommand::Prefetch {
+        error_codes,
+        from_task,
+        lang,
+        cache,
+    }) = args.command
+    {
+        let language = lang.ok_or_else(missing_lang)?;
+        return prefetch_wiki(
+            &pecker,
+            &language,
+            &cache,
+            &error_codes,
+            from_task.as_deref(),
+        )
+        .await;
+    }
+    let error_code = args
+        .error_code
+        .ok_or_else(|| CodepeckerError::MissingParam("errorCode".to_owned()))?;
+    let language = args.lang.ok_or_else(missing_lang)?;
+    let mut enricher = enricher(&pecker, &language, &args.cache);
+    let solution = enricher.solution(&error_code).await.ok_or_else(|| {
+        CodepeckerError::CustomInvalidInfo(format!("没有{language}语言{error_code}的wiki"))
+    })?;
+    println!("{}", solution.wiki_description);
+    for text in [&solution.wiki_detail, &solution.wiki_example] {
+        if !text.is_empty() {
+            println!("\n{text}");
+        }
+    }
+    Ok(())
+}
+
+// 从服务端下载源文件, 未指定保存的文件时输出到标准输出
+async fn file(global: &GlobalArgs, args: FileArgs) -> Result<(), CodepeckerError> {
+    let pecker = client(global, None, None)?;
+    let content = pecker.get_file_content_bytes(&args.path).await?;
+    let bytes: Vec<u8> = content
+        .byte_array_of_files
+        .iter()
+        .map(|b| *b as u8)
+        .collect();
+    match &args.output {
+        Some(output) => {
+            fs::write(output, &bytes)?;
+            log::info!("将文件{}保存到{}完成!", args.path, output.display());
+        }
+        None => io::stdout().write_all(&bytes)?,
+    }
+    Ok(())
+}
+
+// 按wiki缓存参数创建 Enricher
+fn enricher<'a>(pecker: &'a PeckerClient, language: &'a str, args: &WikiCacheArgs) -> Enricher<'a> {
+    let ttl = args
+        .wiki_ttl
+        .map_or(Duration::ZERO, |HumanDuration(ttl)| ttl);
+    let cache = WikiCache::new(pecker.url(), language, ttl);
+    Enricher::new(pecker, language, args.concurrency.unwrap_or(1))
+        .wiki_cache(cache, args.offline_wiki == Some(true))
+}
+
+// 获取检测结果并写入文件, 设置了质量门禁时检查门禁
+async fn fetch_results(
+    pecker: &PeckerClient,
+    task: &str,
+    language: &str,
+    args: &ResultArgs,
+) -> Result<(), CodepeckerError> {
+    // 未指定忽略文件时, 当前目录下存在默认忽略文件则使用
+    let suppressions = match &args.ignore_file {
+        Some(path) => Some(Suppressions::load(path)?),
+        None if Path::new(DEFAULT_IGNORE_FILE).exists() => {
+            Some(Suppressions::load(Path::new(DEFAULT_IGNORE_FILE))?)
+        }
+        None => None,
+    };
+    let baseline = match (&args.baseline, &args.baseline_task) {
+        (Some(_), Some(_)) => {
+            return Err(CodepeckerError::InvalidConfig(
+                "baseline 与 baseline-task 不能同时设置".to_owned(),
+            ))
+        }
+        (Some(file), None) => Some(Baseline::File(file.clone())),
+        (None, Some(task)) => Some(Baseline::Task(task.clone())),
+        (None, None) => None,
+    };
+    let reports = match &args.report {
+        Some(reports) if !reports.is_empty() => reports.clone(),
+        _ => vec![ReportTarget {
+            format: args.format.clone().unwrap_or_else(|| "json".to_owned()),
+            path: args
+                .output
+                .clone()
+                .unwrap_or_else(|| "results.json".to_owned()),
+        }],
+    };
+    // 输出 SBOM 或设置了开源组件的质量门禁时需要开源组件检测结果
+    let components = args.components == Some(true)
+        || args.fail_on_components.is_some()
+        || reports.iter().any(|r| r.format == "cyclonedx");
+    let options = ResultOptions {
+        language,
+        filter: ProblemFilter::new(&FilterOptions {
+            severity: args.severity.unwrap_or_default(),
+            include_error_code: args.include_error_code.clone().unwrap_or_default(),
+            exclude_error_code: args.exclude_error_code.clone().unwrap_or_default(),
+            include_path: args.include_path.clone().unwrap_or_default(),
+            exclude_path: args.exclude_path.clone().unwrap_or_default(),
+            category: args.category.clone().unwrap_or_default(),
+            cwe: args.cwe.clone().unwrap_or_default(),
+        })?,
+        severity_map: args.severity_map.clone().unwrap_or_default(),
+        component_severity: components.then(|| {
+            args.component_severity
+                .unwrap_or(SeverityFilter::AtLeast(Severity::Unknown))
+        }),
+        reports,
+        get_source: args.get_source == Some(true),
+        snippet_lines: args.snippet_lines.unwrap_or(3),
+        fail_on: args.fail_on.as_ref(),
+        summary: SummaryOptions {
+            top: args.summary.summary_top.unwrap_or(10),
+            limit: args.summary.summary_limit.unwrap_or(65000),
+            repo_url: args.summary.repo_url.as_deref(),
+            commit_sha: args.summary.commit_sha.as_deref(),
+        },
+        wiki: &args.wiki,
+        suppressions: suppressions.as_ref(),
+        baseline,
+    };
+    let result = get_task_result(pecker, task, &options).await?;
+    if let Some(path) = &args.summary.summary_file {
+        report::write_summary(path, &result, &options.summary)?;
+    }
+    // 缺陷及开源组件的质量门禁都检查完再返回, 便于在日志中看到全部统计
+    let problems_gate = args.fail_on.as_ref().map_or(Ok(()), |gate| {
+        gate.check(
+            "缺陷",
+            result
+                .gated_problems()
+                .map(|p| result.severity_map.severity(p.severity_level)),
+        )
+    });
+    let components_gate = match (&args.fail_on_components, &result.components) {
+        (Some(gate), Some(components)) => gate.check(
+            "开源组件",
+            components
+                .components
+                .iter()
+                .map(|c| result.severity_map.severity(c.severity_level)),
+        ),
+        _ => Ok(()),
+    };
+    problems_gate.and(components_gate)
+}
+
+// 获取检测结果时的参数
+struct ResultOptions<'a> {
+    language: &'a str,
+    filter: ProblemFilter,
+    severity_map: SeverityMap,
+    // 获取开源组件检测结果时组件的级别
+    component_severity: Option<SeverityFilter>,
+    reports: Vec<ReportTarget>,
+    get_source: bool,
+    snippet_lines: u64,
+    fail_on: Option<&'a QualityGate>,
+    summary: SummaryOptions<'a>,
+    wiki: &'a WikiCacheArgs,
+    suppressions: Option<&'a Suppressions>,
+    baseline: Option<Baseline>,
+}
+
+// 获取指定的errorCode或检测任务中缺陷的errorCode的wiki, 均未指定时获取该语言全部规则的wiki, 并写入本地缓存
+async fn prefetch_wiki(
+    pecker: &PeckerClient,
+    language: &str,
+    args: &WikiCacheArgs,
+    error_codes: &[String],
+    from_task: Option<&str>,
+) -> Result<(), CodepeckerError> {
+    let mut codes: BTreeSet<String> = error_codes.iter().cloned().collect();
+    if let Some(task) = from_task {
+        let problems = pecker.get_task_problems(task).await?;
+        codes.extend(problems.into_iter().map(|p| p.error_code));
+    }
+    if error_codes.is_empty() && from_task.is_none() {
+        codes.extend(pecker.get_rules(language).await?);
+    }
+    if codes.is_empty() {
+        log::warn!("没有需要缓存的errorCode");
+        return Ok(());
+    }
+    let ttl = args
+        .wiki_ttl
+        .map_or(Duration::ZERO, |HumanDuration(ttl)| ttl);
+    let cache = WikiCache::new(pecker.url(), language, ttl).ok_or_else(|| {
+        CodepeckerError::CustomInvalidInfo("无法确定用户缓存目录, 不能缓存wiki".to_owned())
+    })?;
+    let total = codes.len();
+    let concurrency = args.concurrency.unwrap_or(1);
+    let mut enricher = Enricher::new(pecker, language, concurrency).wiki_cache(Some(cache), false);
+    let cached = enricher.prefetch(codes).await;
+    log::info!("缓存{language}语言{total}个errorCode的wiki完成, 其中{cached}个有解决方案");
+    Ok(())
+}
+
+// 获取检测结果并写入文件, 返回的结果供质量门禁使用
+async fn get_task_result(
+    pecker: &PeckerClient,
+    task: &str,
+    options: &ResultOptions<'_>,
+) -> Result<TaskResult, CodepeckerError> {
+    let ResultOptions {
+        language,
+        get_source,
+        snippet_lines,
+        ..
+    } = *options;
+    let severity = options.filter.severity();
+    let severity_map = &options.severity_map;
+    let info = pecker.query_statistics(task).await?;
+    let all_defects = pecker.get_task_problems(task).await?;
+    let mut filter_problems = options.filter.apply("缺陷", severity_map, all_defects);
+    // 为缺陷添加solution(包括：wiki_description,wiki_detail,wiki_example 字段), 根据get_source参数决定是否获取源文件
+    let start = Instant::now();
+    let mut enricher = enricher(pecker, language, options.wiki);
+    enricher.solutions(&mut filter_problems).await;
+    if get_source {
+        enricher.sources(&mut filter_problems, snippet_lines).await;
+    }
+    log::info!(
+        "补充{}个缺陷的解决方案及源文件完成, 耗时{:.2}秒",
+        filter_problems.len(),
+        start.elapsed().as_secs_f64()
+    );
+    let problem_count = filter_problems.len();
+    log::info!("筛选级别为{severity}的缺陷或漏洞,数量为{problem_count}个");
+    enricher.fingerprints(&mut filter_problems).await;
+    if let Some(suppressions) = options.suppressions {
+        suppressions.apply(&mut filter_problems);
+    }
+
+    // 与基线对比, 基线文件及基线任务的缺陷按相同的条件筛选
+    let fixed = match &options.baseline {
+        Some(base) => {
+            let base_problems = match base {
+                Baseline::File(path) => baseline::load(path)?,
+                Baseline::Task(base_task) => {
+                    log::info!("获取基线任务{base_task}的检测结果");
+                    pecker.get_task_problems(base_task).await?
+                }
+            };
+            let mut base_problems = options
+                .filter
+                .apply("基线缺陷", severity_map, base_problems);
+            enricher.fingerprints(&mut base_problems).await;
+            Some(baseline::diff(&mut filter_problems, base_problems))
+        }
+        None => None,
+    };
+
+    // 按级别筛选的组件用于结果及质量门禁, SBOM 使用全部组件
+    let mut all_components = Vec::new();
+    let components = match options.component_severity {
+        Some(component_severity) => {
+            let mut components = pecker.query_task_jars_detection_result(task).await?;
+            all_components = components.components.clone();
+            components.components = filter::by_severity(
+                "开源组件",
+                component_severity,
+                severity_map,
+                components.components,
+                |c| c.severity_level,
+            );
+            log::info!(
+                "筛选级别为{component_severity}的开源组件,数量为{}个",
+                components.components.len()
+            );
+            Some(components)
+        }
+        None => None,
+    };
+
+    // 获取源文件时, 缺陷只保留源码片段, 完整的文件内容按路径去重后放入 files 表
+    // 只为计算指纹获取的文件不输出
+    let mut files = BTreeMap::new();
+    if get_source {
+        files = enricher.files(&filter_problems);
+        if let Some(fixed) = &fixed {
+            files.extend(enricher.files(fixed));
+        }
+    }
+    let result = TaskResult {
+        task_id: task.to_string(),
+        severity,
+        info,
+        problems: filter_problems,
+        fixed,
+        files,
+        gate: options.fail_on.cloned(),
+        components,
+        all_components,
+        severity_map: severity_map.clone(),
+    };
+    // 结果只获取一次, 依次写入各个报告
+    for report in &options.reports {
+        report::write(&report.format, &report.path, &result, &options.summary)?;
+    }
+    Ok(result)
+}
@@ -0,0 +1,217 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    time::Instant,
+};
+
+use futures_util::{stream, StreamExt};
+
+use crate::{
+    model::{Problem, Snippet, Solution, SourceFile},
+    wiki::WikiCache,
+    PeckerClient,
+};
+
+/// 为缺陷补充解决方案及源文件内容, 相同的errorCode及文件只获取一次
+pub(crate) struct Enricher<'a> {
+    pecker: &'a PeckerClient,
+    language: &'a str,
+    concurrency: usize,
+    // (errorCode, language) 对应的解决方案, 获取失败时为 None
+    solutions: HashMap<(String, String), Option<Solution>>,
+    // 文件路径对应的解码后的源文件, 获取失败时为 None
+    files: HashMap<String, Option<SourceFile>>,
+    cache: Option<WikiCache>,
+    offline: bool,
+}
+
+impl<'a> Enricher<'a> {
+    pub(crate) fn new(pecker: &'a PeckerClient, language: &'a str, concurrency: usize) -> Self {
+        Self {
+            pecker,
+            language,
+            concurrency: concurrency.max(1),
+            solutions: HashMap::new(),
+            files: HashMap::new(),
+            cache: None,
+            offline: false,
+        }
+    }
+
+    // 使用wiki缓存, offline 为 true 时只使用缓存(包括已过期的缓存), 不请求服务端
+    pub(crate) fn wiki_cache(mut self, cache: Option<WikiCache>, offline: bool) -> Self {
+        self.cache = cache;
+        self.offline = offline;
+        self
+    }
+
+    // 并发获取errorCode的解决方案, 优先使用wiki缓存
+    async fn fetch_solutions(&mut self, codes: BTreeSet<String>) {
+        let start = Instant::now();
+        let count = codes.len();
+        let (pecker, language, offline) = (self.pecker, self.language, self.offline);
+        let cache = self.cache.as_ref();
+        let fetched: Vec<(String, Option<Solution>)> = stream::iter(codes)
+            .map(|code| async move {
+                if let Some(wiki) = cache.and_then(|c| c.get(&code, offline)) {
+                    return (code, wiki.into_solution());
+                }
+                if offline {
+                    log::debug!("离线模式下没有{code}的wiki缓存");
+                    return (code, None);
+                }
+                log::debug!("获取解决方案详情,errorCode:{code},language:{language}");
+                let solution = match pecker.get_solution_detail(&code, language).await {
+                    Ok(wiki) => {
+                        if let Some(cache) = cache {
+                            cache.put(&code, &wiki);
+                        }
+                        wiki.into_solution()
+                    }
+                    Err(e) => {
+                        log::warn!("获取{code}的解决方案失败: {e}");
+                        None
+                    }
+                };
+                (code, solution)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+        for (code, solution) in fetched {
+            self.solutions.insert((code, language.to_owned()), solution);
+        }
+        log::info!(
+            "获取{count}个errorCode的解决方案完成, 耗时{:.2}秒",
+            start.elapsed().as_secs_f64()
+        );
+    }
+
+    // 获取尚未获取过的errorCode的解决方案, 并添加到缺陷中
+    pub(crate) async fn solutions(&mut self, problems: &mut [Problem]) {
+        let language = self.language;
+        let codes = problems
+            .iter()
+            .map(|p| p.error_code.clone())
+            .filter(|code| {
+                !self
+                    .solutions
+                    .contains_key(&(code.clone(), language.to_owned()))
+            })
+            .collect();
+        self.fetch_solutions(codes).await;
+        for problem in problems.iter_mut() {
+            let key = (problem.error_code.clone(), language.to_owned());
+            problem.solution = self.solutions.get(&key).cloned().flatten();
+        }
+    }
+
+    // 获取errorCode的解决方案并写入wiki缓存, 返回有解决方案的errorCode数量
+    pub(crate) async fn prefetch(&mut self, codes: BTreeSet<String>) -> usize {
+        self.fetch_solutions(codes).await;
+        self.solutions.values().filter(|s| s.is_some()).count()
+    }
+
+    // 获取单个errorCode的解决方案
+    pub(crate) async fn solution(&mut self, error_code: &str) -> Option<Solution> {
+        let key = (error_code.to_owned(), self.language.to_owned());
+        if !self.solutions.contains_key(&key) {
+            self.fetch_solutions(BTreeSet::from([error_code.to_owned()]))
+                .await;
+        }
+        self.solutions.get(&key).cloned().flatten()
+    }
+
+    // 并发获取尚未获取过的源文件, 返回本次获取的文件数量
+    async fn fetch_files<'p>(&mut self, paths: impl Iterator<Item = &'p str>) -> usize {
+        let paths: BTreeSet<&str> = paths
+            .filter(|path| !self.files.contains_key(*path))
+            .collect();
+        let count = paths.len();
+        let pecker = self.pecker;
+        let fetched: Vec<(String, Option<SourceFile>)> = stream::iter(paths)
+            .map(|path| async move {
+                log::debug!("获取文件内容的字节数组,filePath:{path}");
+                let file = match pecker.get_file_content_bytes(path).await {
+                    Ok(file_content) => {
+                        let file = SourceFile::decode(&file_content.byte_array_of_files);
+                        log::debug!("文件{path}的编码为{}", file.encoding);
+                        Some(file)
+                    }
+                    Err(e) => {
+                        log::warn!("获取文件{path}的内容失败: {e}");
+                        None
+                    }
+                };
+                (path.to_owned(), file)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+        self.files.extend(fetched);
+        count
+    }
+
+    // 并发获取缺陷及其跟踪步骤涉及的源文件, 并为缺陷添加所在行前后各 context 行的源码片段
+    pub(crate) async fn sources(&mut self, problems: &mut [Problem], context: u64) {
+        let start = Instant::now();
+        let count = self.fetch_files(paths(problems)).await;
+        for problem in problems.iter_mut() {
+            problem.snippet = self.snippet(problem.file_path.as_ref(), problem.line, context);
+            for trace_block in &mut problem.trace_block {
+                trace_block.snippet =
+                    self.snippet(trace_block.file.as_ref(), trace_block.line, context);
+            }
+        }
+        log::info!(
+            "获取{count}个源文件完成, 耗时{:.2}秒",
+            start.elapsed().as_secs_f64()
+        );
+    }
+
+    // 为缺陷计算指纹, 基线文件中已有指纹的保持不变. 指纹包括缺陷所在行的源码,
+    // 不论是否获取源文件(--get-source)都获取缺陷所在的文件, 已获取过的文件不再重复获取
+    pub(crate) async fn fingerprints(&mut self, problems: &mut [Problem]) {
+        let paths = problems
+            .iter()
+            .filter(|p| p.fingerprint.is_none())
+            .filter_map(|p| p.file_path.as_deref());
+        let count = self.fetch_files(paths).await;
+        log::debug!("为计算缺陷指纹获取{count}个源文件");
+        for problem in problems.iter_mut().filter(|p| p.fingerprint.is_none()) {
+            let code_line = problem
+                .file_path
+                .as_ref()
+                .and_then(|path| self.files.get(path)?.as_ref())
+                .zip(problem.line)
+                .and_then(|(file, line)| file.line(line));
+            problem.fingerprint = Some(problem.compute_fingerprint(code_line));
+        }
+    }
+
+    fn snippet(&self, path: Option<&String>, line: Option<u64>, context: u64) -> Option<Snippet> {
+        let file = self.files.get(path?)?.as_ref()?;
+        file.snippet(line?, context)
+    }
+
+    // 缺陷及其跟踪步骤涉及的、已获取的源文件, 按路径去重
+    pub(crate) fn files(&self, problems: &[Problem]) -> BTreeMap<String, SourceFile> {
+        paths(problems)
+            .filter_map(|path| {
+                let file = self.files.get(path)?.clone()?;
+                Some((path.to_owned(), file))
+            })
+            .collect()
+    }
+}
+
+// 缺陷及其跟踪步骤涉及的文件路径
+fn paths(problems: &[Problem]) -> impl Iterator<Item = &str> {
+    problems
+        .iter()
+        .flat_map(|p| {
+            p.file_path
+                .iter()
+                .chain(p.trace_block.iter().filter_map(|t| t.file.as_ref()))
+        })
+        .map(String::as_str)
+}
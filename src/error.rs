@@ -4,12 +4,23 @@ use std::io;
 
 use thiserror::Error;
 
+use crate::progress::human_size;
+
 #[derive(Error, Debug)]
 pub enum CodepeckerError {
+    #[deprecated(since = "0.2.0", note = "使用 MissingParam, 错误信息中包含缺少的参数")]
     #[error("输入指定的参数不满足要求")]
     ParamMissing,
-    #[error("Url: {0} 的API地址错误或无法连接")]
-    UnableToConnect(String),
+    #[error("缺少参数: {0}")]
+    MissingParam(String),
+    #[error("配置错误: {0}")]
+    InvalidConfig(String),
+    #[error("Url: {url} 的API地址错误或无法连接: {source}")]
+    UnableToConnect {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
     #[error("无法获取Text数据")]
     UnableToGetText,
     #[error("无法解析json数据, 请检查URL地址及key值")]
@@ -28,4 +39,41 @@ pub enum CodepeckerError {
     IoError(#[from] io::Error),
     #[error(transparent)]
     SerdeError(#[from] serde_json::Error),
+    #[error("无法解析接口 {url} 的响应, 字段 {field}: {reason}")]
+    InvalidResponse {
+        url: String,
+        field: String,
+        reason: String,
+    },
+    #[error(
+        "压缩包 {path} 的大小 {} 超过上限 {}",
+        human_size(*size),
+        human_size(*limit)
+    )]
+    ArchiveTooLarge { path: String, size: u64, limit: u64 },
+    #[error("检测任务 {task} 在 {timeout} 秒内未完成, 最后的状态: {last_status}")]
+    ScanTimeout {
+        task: String,
+        timeout: u64,
+        last_status: String,
+    },
+    #[error("质量门禁未通过: {0}")]
+    GateFailed(String),
+}
+
+impl CodepeckerError {
+    /// 进程退出码: 1 质量门禁未通过, 2 参数错误, 3 扫描错误, 4 连接错误, 5 扫描超时
+    #[allow(deprecated)]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::GateFailed(_) => 1,
+            Self::ParamMissing
+            | Self::MissingParam(_)
+            | Self::InvalidConfig(_)
+            | Self::ArchiveTooLarge { .. } => 2,
+            Self::UnableToConnect { .. } | Self::ClientBuildError | Self::ProxyBuildError => 4,
+            Self::ScanTimeout { .. } => 5,
+            _ => 3,
+        }
+    }
 }
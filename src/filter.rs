@@ -0,0 +1,338 @@
+use std::collections::BTreeSet;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::{
+    error::CodepeckerError,
+    model::Problem,
+    severity::{Severity, SeverityFilter, SeverityMap},
+};
+
+/// 缺陷的筛选条件, 未设置的条件不筛选
+#[derive(Debug, Clone)]
+pub(crate) struct FilterOptions {
+    pub(crate) severity: SeverityFilter,
+    /// 只保留的errorCode
+    pub(crate) include_error_code: Vec<String>,
+    /// 排除的errorCode
+    pub(crate) exclude_error_code: Vec<String>,
+    /// 只保留路径匹配的缺陷, eg. "src/**"
+    pub(crate) include_path: Vec<String>,
+    /// 排除路径匹配的缺陷, eg. "**/test/**"
+    pub(crate) exclude_path: Vec<String>,
+    /// 只保留的缺陷类别, 不区分大小写
+    pub(crate) category: Vec<String>,
+    /// 只保留的 CWE 编号, eg. CWE-89 或 89
+    pub(crate) cwe: Vec<String>,
+}
+
+// 筛选链中的一个条件
+enum Condition {
+    ErrorCode {
+        include: BTreeSet<String>,
+        exclude: BTreeSet<String>,
+    },
+    Path {
+        include: Option<GlobSet>,
+        exclude: GlobSet,
+    },
+    Category(Vec<String>),
+    Cwe(BTreeSet<String>),
+}
+
+impl Condition {
+    fn name(&self) -> &'static str {
+        match self {
+            Condition::ErrorCode { .. } => "errorCode",
+            Condition::Path { .. } => "路径",
+            Condition::Category(_) => "类别",
+            Condition::Cwe(_) => "CWE",
+        }
+    }
+
+    // 没有路径、类别或 CWE 的缺陷不满足对应的条件
+    fn matches(&self, problem: &Problem) -> bool {
+        match self {
+            Condition::ErrorCode { include, exclude } => {
+                (include.is_empty() || include.contains(&problem.error_code))
+                    && !exclude.contains(&problem.error_code)
+            }
+            Condition::Path { include, exclude } => {
+                let Some(path) = problem.file_path.as_ref() else {
+                    return include.is_none();
+                };
+                let path = path.replace('\\', "/");
+                include.as_ref().is_none_or(|glob| glob.is_match(&path)) && !exclude.is_match(&path)
+            }
+            Condition::Category(categories) => problem.category().is_some_and(|category| {
+                categories.iter().any(|c| c.eq_ignore_ascii_case(category))
+            }),
+            Condition::Cwe(cwes) => problem.cwe().is_some_and(|cwe| cwes.contains(&cwe)),
+        }
+    }
+}
+
+// 路径匹配规则与忽略文件相同, * 不匹配路径分隔符
+fn glob_set(option: &str, patterns: &[String]) -> Result<GlobSet, CodepeckerError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| {
+                CodepeckerError::InvalidConfig(format!("{option}的匹配规则{pattern}不合法: {e}"))
+            })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| CodepeckerError::InvalidConfig(e.to_string()))
+}
+
+// CWE 编号只保留数字, 与 [`Problem::cwe`] 一致
+fn parse_cwe(cwe: &str) -> Result<String, CodepeckerError> {
+    let cwe = cwe.trim();
+    let id = cwe
+        .strip_prefix("CWE-")
+        .or_else(|| cwe.strip_prefix("cwe-"))
+        .unwrap_or(cwe);
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(CodepeckerError::InvalidConfig(format!(
+            "CWE编号 {cwe} 不合法, eg. CWE-89 或 89"
+        )));
+    }
+    Ok(id.to_owned())
+}
+
+/// 缺陷的筛选链: 先按级别筛选, 再依次按errorCode、路径、类别及 CWE 筛选
+pub(crate) struct ProblemFilter {
+    severity: SeverityFilter,
+    conditions: Vec<Condition>,
+}
+
+impl ProblemFilter {
+    pub(crate) fn new(options: &FilterOptions) -> Result<Self, CodepeckerError> {
+        let mut conditions = Vec::new();
+        if !options.include_error_code.is_empty() || !options.exclude_error_code.is_empty() {
+            conditions.push(Condition::ErrorCode {
+                include: options.include_error_code.iter().cloned().collect(),
+                exclude: options.exclude_error_code.iter().cloned().collect(),
+            });
+        }
+        if !options.include_path.is_empty() || !options.exclude_path.is_empty() {
+            let include = if options.include_path.is_empty() {
+                None
+            } else {
+                Some(glob_set("include-path", &options.include_path)?)
+            };
+            conditions.push(Condition::Path {
+                include,
+                exclude: glob_set("exclude-path", &options.exclude_path)?,
+            });
+        }
+        if !options.category.is_empty() {
+            conditions.push(Condition::Category(options.category.clone()));
+        }
+        if !options.cwe.is_empty() {
+            let cwes = options
+                .cwe
+                .iter()
+                .map(|cwe| parse_cwe(cwe))
+                .collect::<Result<_, _>>()?;
+            conditions.push(Condition::Cwe(cwes));
+        }
+        Ok(Self {
+            severity: options.severity,
+            conditions,
+        })
+    }
+
+    pub(crate) fn severity(&self) -> SeverityFilter {
+        self.severity
+    }
+
+    /// 依次应用各筛选条件, 并在日志中输出每个条件筛除的数量
+    pub(crate) fn apply(
+        &self,
+        subject: &str,
+        map: &SeverityMap,
+        problems: Vec<Problem>,
+    ) -> Vec<Problem> {
+        let mut problems = by_severity(subject, self.severity, map, problems, |p| p.severity_level);
+        for condition in &self.conditions {
+            let before = problems.len();
+            problems.retain(|problem| condition.matches(problem));
+            log::debug!(
+                "按{}筛除{}个{subject}, 剩余{}个",
+                condition.name(),
+                before - problems.len(),
+                problems.len()
+            );
+        }
+        problems
+    }
+}
+
+/// 按级别筛选, 未知级别(没有或无法对应的 severityLevel)只在筛选条件包括 unknown 时保留
+pub(crate) fn by_severity<T>(
+    subject: &str,
+    filter: SeverityFilter,
+    map: &SeverityMap,
+    items: Vec<T>,
+    severity_level: impl Fn(&T) -> Option<i64>,
+) -> Vec<T> {
+    let mut unknown = BTreeSet::new();
+    let mut unknown_count = 0;
+    let filtered = items
+        .into_iter()
+        .filter(|item| {
+            let level = severity_level(item);
+            let severity = map.severity(level);
+            if severity == Severity::Unknown {
+                unknown_count += 1;
+                unknown.insert(level.map_or_else(|| "无".to_owned(), |l| l.to_string()));
+            }
+            filter.matches(severity)
+        })
+        .collect();
+    if unknown_count > 0 {
+        let levels: Vec<String> = unknown.into_iter().collect();
+        log::warn!(
+            "{unknown_count}个{subject}的级别未知(severityLevel: {}), {}",
+            levels.join(","),
+            if filter.matches(Severity::Unknown) {
+                "按unknown级别保留"
+            } else {
+                "已筛除, 可通过 --severity-map 设置对应关系或在筛选条件中包括unknown"
+            }
+        );
+    }
+    filtered
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::test_problem;
+
+    fn empty_options() -> FilterOptions {
+        FilterOptions {
+            severity: SeverityFilter::default(),
+            include_error_code: Vec::new(),
+            exclude_error_code: Vec::new(),
+            include_path: Vec::new(),
+            exclude_path: Vec::new(),
+            category: Vec::new(),
+            cwe: Vec::new(),
+        }
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    // 筛选后剩余缺陷的行号
+    fn lines(options: &FilterOptions, problems: Vec<Problem>) -> Vec<u64> {
+        ProblemFilter::new(options)
+            .unwrap()
+            .apply("缺陷", &SeverityMap::default(), problems)
+            .iter()
+            .filter_map(|p| p.line)
+            .collect()
+    }
+
+    #[test]
+    fn filter_by_error_code() {
+        let problems = || {
+            vec![
+                test_problem(json!({ "line": 1, "severityLevel": 1 })),
+                test_problem(json!({ "line": 2, "severityLevel": 1, "errorCode": "SQLI" })),
+                test_problem(json!({ "line": 3, "severityLevel": 1, "errorCode": "LOG" })),
+            ]
+        };
+        let mut options = empty_options();
+        options.include_error_code = strings(&["NPE", "SQLI"]);
+        assert_eq!(lines(&options, problems()), [1, 2]);
+        options.exclude_error_code = strings(&["SQLI"]);
+        assert_eq!(lines(&options, problems()), [1]);
+    }
+
+    #[test]
+    fn filter_by_path() {
+        let problems = || {
+            vec![
+                test_problem(
+                    json!({ "line": 1, "severityLevel": 1, "filePath": "src\\main\\A.java" }),
+                ),
+                test_problem(
+                    json!({ "line": 2, "severityLevel": 1, "filePath": "src/test/ATest.java" }),
+                ),
+                test_problem(json!({ "line": 3, "severityLevel": 1, "filePath": "lib/B.java" })),
+                test_problem(json!({ "line": 4, "severityLevel": 1, "filePath": null })),
+            ]
+        };
+        let mut options = empty_options();
+        options.exclude_path = strings(&["**/test/**"]);
+        assert_eq!(lines(&options, problems()), [1, 3, 4]);
+        options.include_path = strings(&["src/**"]);
+        assert_eq!(lines(&options, problems()), [1]);
+        // * 不匹配路径分隔符
+        options.include_path = strings(&["src/*.java"]);
+        assert!(lines(&options, problems()).is_empty());
+    }
+
+    #[test]
+    fn filter_by_category_and_cwe() {
+        let problems = || {
+            vec![
+                test_problem(
+                    json!({ "line": 1, "severityLevel": 1, "category": "Injection", "cwe": "CWE-89" }),
+                ),
+                test_problem(
+                    json!({ "line": 2, "severityLevel": 1, "defectCategory": "injection", "cwe": 79 }),
+                ),
+                test_problem(json!({ "line": 3, "severityLevel": 1 })),
+            ]
+        };
+        let mut options = empty_options();
+        options.category = strings(&["INJECTION"]);
+        assert_eq!(lines(&options, problems()), [1, 2]);
+        options.cwe = strings(&["89", "cwe-22"]);
+        assert_eq!(lines(&options, problems()), [1]);
+    }
+
+    #[test]
+    fn unknown_severity_kept_only_when_requested() {
+        let problems = || {
+            vec![
+                test_problem(json!({ "line": 1, "severityLevel": 4 })),
+                test_problem(json!({ "line": 2, "severityLevel": 9 })),
+                test_problem(json!({ "line": 3 })),
+            ]
+        };
+        let mut options = empty_options();
+        assert_eq!(lines(&options, problems()), [1]);
+        options.severity = "unknown".parse().unwrap();
+        assert_eq!(lines(&options, problems()), [1, 2, 3]);
+        options.severity = "=unknown".parse().unwrap();
+        assert_eq!(lines(&options, problems()), [2, 3]);
+    }
+
+    #[test]
+    fn reject_invalid_conditions() {
+        let mut options = empty_options();
+        options.cwe = strings(&["SQL-89"]);
+        assert!(matches!(
+            ProblemFilter::new(&options),
+            Err(CodepeckerError::InvalidConfig(_))
+        ));
+        let mut options = empty_options();
+        options.exclude_path = strings(&["src/["]);
+        assert!(matches!(
+            ProblemFilter::new(&options),
+            Err(CodepeckerError::InvalidConfig(_))
+        ));
+    }
+}
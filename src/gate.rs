@@ -0,0 +1,152 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{error::CodepeckerError, severity::Severity};
+
+/// 质量门禁: 各缺陷级别允许的最大数量, eg. critical=0,high=5
+#[derive(Debug, Clone)]
+pub(crate) struct QualityGate {
+    limits: Vec<(Severity, usize)>,
+}
+
+impl FromStr for QualityGate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut limits = Vec::new();
+        for item in s.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+            let (severity, limit) = item
+                .split_once('=')
+                .ok_or_else(|| format!("门禁规则 {item} 的格式应为 级别=数量"))?;
+            let severity: Severity = severity.parse()?;
+            let limit = limit
+                .trim()
+                .parse()
+                .map_err(|_| format!("门禁规则 {item} 的数量必须是非负整数"))?;
+            limits.push((severity, limit));
+        }
+        if limits.is_empty() {
+            return Err("门禁规则不能为空".to_owned());
+        }
+        Ok(Self { limits })
+    }
+}
+
+impl fmt::Display for QualityGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limits: Vec<String> = self
+            .limits
+            .iter()
+            .map(|(severity, limit)| format!("{severity}={limit}"))
+            .collect();
+        write!(f, "{}", limits.join(","))
+    }
+}
+
+impl Serialize for QualityGate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// 配置文件中与命令行参数的格式相同, eg. fail_on = "critical=0,high=5"
+impl<'de> Deserialize<'de> for QualityGate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl QualityGate {
+    // 各门禁规则的统计: (级别, 实际数量, 阈值)
+    fn tally(
+        &self,
+        severities: impl IntoIterator<Item = Severity>,
+    ) -> Vec<(Severity, usize, usize)> {
+        let mut counts: HashMap<Severity, usize> = HashMap::new();
+        for severity in severities {
+            *counts.entry(severity).or_default() += 1;
+        }
+        self.limits
+            .iter()
+            .map(|(severity, limit)| {
+                let count = counts.get(severity).copied().unwrap_or_default();
+                (*severity, count, *limit)
+            })
+            .collect()
+    }
+
+    // 数量超出阈值的级别
+    pub(crate) fn exceeded(&self, severities: impl IntoIterator<Item = Severity>) -> Vec<Severity> {
+        self.tally(severities)
+            .into_iter()
+            .filter(|(_, count, limit)| count > limit)
+            .map(|(severity, _, _)| severity)
+            .collect()
+    }
+
+    // 统计各级别的数量, 超出阈值时返回 GateFailed, subject 为统计对象, eg. 缺陷、开源组件
+    pub(crate) fn check(
+        &self,
+        subject: &str,
+        severities: impl IntoIterator<Item = Severity>,
+    ) -> Result<(), CodepeckerError> {
+        let mut violations = Vec::new();
+        let mut summary = Vec::new();
+        for (name, count, limit) in self.tally(severities) {
+            summary.push(format!("{name}: {count}/{limit}"));
+            if count > limit {
+                violations.push(format!("{subject} {name} 数量 {count} 超过阈值 {limit}"));
+            }
+        }
+        log::info!("{subject}质量门禁统计(实际/阈值): {}", summary.join(", "));
+        if violations.is_empty() {
+            log::info!("{subject}质量门禁通过");
+            Ok(())
+        } else {
+            Err(CodepeckerError::GateFailed(violations.join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_quality_gate() {
+        let gate: QualityGate = " critical=0, HIGH=5,,unknown=1 ".parse().unwrap();
+        assert_eq!(
+            gate.limits,
+            [
+                (Severity::Critical, 0),
+                (Severity::High, 5),
+                (Severity::Unknown, 1)
+            ]
+        );
+        assert_eq!(gate.to_string(), "critical=0,high=5,unknown=1");
+    }
+
+    #[test]
+    fn reject_invalid_quality_gate() {
+        for s in [
+            "", " , ", "high", "high=", "high=-1", "high=1.5", "urgent=0", "=0",
+        ] {
+            assert!(s.parse::<QualityGate>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn exceeded_counts_each_level_exactly() {
+        let gate: QualityGate = "critical=0,high=1".parse().unwrap();
+        let severities = [Severity::High, Severity::High, Severity::Medium];
+        assert_eq!(gate.exceeded(severities), [Severity::High]);
+        assert!(gate.check("缺陷", [Severity::High, Severity::Low]).is_ok());
+        assert!(gate.check("缺陷", [Severity::Critical]).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 #[tokio::main]
-async fn main() -> Result<(), codepecker::error::CodepeckerError> {
+async fn main() {
     if let Err(e) = codepecker::builder().await {
         eprintln!("程序运行出错: {}", e);
+        std::process::exit(e.exit_code());
     }
-    Ok(())
 }
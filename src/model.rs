@@ -0,0 +1,585 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+// 服务端返回的行号可能是数字或字符串
+fn lenient_line<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Line {
+        Number(u64),
+        Text(String),
+    }
+    match Option::<Line>::deserialize(deserializer)? {
+        Some(Line::Number(line)) => Ok(Some(line)),
+        Some(Line::Text(line)) if line.trim().is_empty() => Ok(None),
+        Some(Line::Text(line)) => line
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom(format!("无法将行号 {line:?} 解析为数字"))),
+        None => Ok(None),
+    }
+}
+
+/// 下发任务接口(postSourceCode / postSourceCodeBySvnGit)的响应
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitResponse {
+    pub status: u64,
+    pub task_id: Option<String>,
+    pub error_msg: Option<String>,
+}
+
+/// 查询任务状态接口(queryTaskStatus)的响应
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskStatusResponse {
+    pub status: u64,
+    pub task_status: Option<String>,
+    pub error_msg: Option<String>,
+}
+
+impl TaskStatusResponse {
+    /// 任务状态的说明
+    pub fn description(&self) -> &'static str {
+        match self.task_status.as_deref() {
+            Some("0") => "代码上传成功",
+            Some("1") => "已解压待检测",
+            Some("2") => "检查中",
+            Some("3") => "检测完成",
+            Some("4") => "检测异常",
+            Some("99") => "排队中",
+            _ => "未知状态",
+        }
+    }
+}
+
+// 输出为 说明(状态码), eg. 排队中(99)
+impl fmt::Display for TaskStatusResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}({})",
+            self.description(),
+            self.task_status.as_deref().unwrap_or("-")
+        )
+    }
+}
+
+/// 检测结果统计接口(queryStatistics)的响应, 统计项随服务端版本变化, 原样保留
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Statistics {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+    #[serde(flatten)]
+    pub counts: Map<String, Value>,
+}
+
+/// 检测结果接口(getTaskResult)的单页响应
+#[derive(Debug, Clone, Deserialize)]
+pub struct TaskResultPage {
+    pub problem: Option<Vec<Problem>>,
+}
+
+/// 缺陷或漏洞, 未建模的字段原样保留在 extra 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Problem {
+    pub error_code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity_level: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "lenient_line",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub line: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trace_block: Vec<TraceBlock>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution: Option<Solution>,
+    /// 缺陷所在行附近的源码片段, 完整文件内容见报告的 files 表
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<Snippet>,
+    /// 缺陷的稳定指纹, 见 [`Problem::compute_fingerprint`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    /// 与基线对比的状态
+    #[serde(
+        rename = "baseline_state",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub baseline_state: Option<BaselineState>,
+    /// 被忽略文件忽略的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppression: Option<Suppression>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 缺陷与基线扫描对比的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BaselineState {
+    /// 本次扫描新增
+    New,
+    /// 基线中已存在
+    Unchanged,
+    /// 基线中存在, 本次扫描已修复
+    Fixed,
+}
+
+/// 缺陷被忽略的原因及过期时间
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suppression {
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+}
+
+// 统一路径分隔符, 避免不同平台上传的源码路径不一致
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+// 去除代码中的空白字符, 避免格式调整影响指纹
+fn normalize_code(code: &str) -> String {
+    code.split_whitespace().collect()
+}
+
+impl Problem {
+    /// 计算缺陷的稳定指纹: errorCode + filePath + 归一化的缺陷所在行源码 + 代码上下文, 不依赖行号.
+    ///
+    /// code_line 为缺陷所在行的源码, 无法获取源文件时为 None. 代码上下文为跟踪步骤的文件及描述,
+    /// 不使用源码片段, 是否获取源文件(--get-source)不影响指纹.
+    pub fn compute_fingerprint(&self, code_line: Option<&str>) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.error_code.as_bytes());
+        hasher.update([0]);
+        hasher.update(normalize_path(self.file_path.as_deref().unwrap_or_default()).as_bytes());
+        hasher.update([0]);
+        hasher.update(normalize_code(code_line.unwrap_or_default()).as_bytes());
+        hasher.update([0]);
+        hasher.update(self.code_context().as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// 服务端返回的 CWE 编号(只保留数字), eg. CWE-89 或 89 均为 89
+    pub fn cwe(&self) -> Option<String> {
+        let cwe = match self.extra.get("cwe")? {
+            Value::String(cwe) => cwe.trim().to_owned(),
+            Value::Number(cwe) => cwe.to_string(),
+            _ => return None,
+        };
+        let id = cwe
+            .strip_prefix("CWE-")
+            .or_else(|| cwe.strip_prefix("cwe-"))
+            .unwrap_or(&cwe);
+        (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then(|| id.to_owned())
+    }
+
+    /// 服务端返回的缺陷类别, 不同版本的字段名为 category 或 defectCategory
+    pub fn category(&self) -> Option<&str> {
+        ["category", "defectCategory"]
+            .into_iter()
+            .find_map(|key| self.extra.get(key)?.as_str())
+            .map(str::trim)
+            .filter(|category| !category.is_empty())
+    }
+
+    fn code_context(&self) -> String {
+        self.trace_block
+            .iter()
+            .map(|trace_block| {
+                format!(
+                    "{}:{}",
+                    normalize_path(trace_block.file.as_deref().unwrap_or_default()),
+                    normalize_code(trace_block.message.as_deref().unwrap_or_default())
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// 数据流缺陷的跟踪步骤
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceBlock {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "lenient_line",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub line: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<Snippet>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 源文件中连续若干行的源码片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    /// 起始行号, 从1开始
+    pub start_line: u64,
+    /// 结束行号(包含)
+    pub end_line: u64,
+    pub code: String,
+}
+
+impl Snippet {
+    /// 片段中第 line 行(文件中的行号)的源码
+    pub fn line(&self, line: u64) -> Option<&str> {
+        let index = line.checked_sub(self.start_line)?;
+        self.code.lines().nth(index as usize)
+    }
+}
+
+/// 解码后的源文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceFile {
+    /// 检测到的编码: UTF-8、GBK 或 GB18030, 无法识别时为 unknown
+    pub encoding: String,
+    pub content: String,
+}
+
+impl SourceFile {
+    /// 解码getFile接口返回的字节数组, 依次尝试 UTF-8(可带BOM)、GBK 及 GB18030
+    pub fn decode(bytes: &[i16]) -> Self {
+        let bytes: Vec<u8> = bytes.iter().map(|b| *b as u8).collect();
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+        if let Ok(content) = std::str::from_utf8(bytes) {
+            return Self::new("UTF-8", content.to_owned());
+        }
+        if let Some(content) =
+            encoding_rs::GB18030.decode_without_bom_handling_and_without_replacement(bytes)
+        {
+            // GB18030 兼容 GBK, 能以 GBK 无损编码回去的视为 GBK
+            let (_, _, unmappable) = encoding_rs::GBK.encode(&content);
+            let encoding = if unmappable { "GB18030" } else { "GBK" };
+            return Self::new(encoding, content.into_owned());
+        }
+        Self::new("unknown", String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn new(encoding: &str, content: String) -> Self {
+        Self {
+            encoding: encoding.to_owned(),
+            content,
+        }
+    }
+
+    /// 第 line 行(从1开始)的源码, 行号超出文件范围时返回 None
+    pub fn line(&self, line: u64) -> Option<&str> {
+        let index = usize::try_from(line.checked_sub(1)?).ok()?;
+        self.content.lines().nth(index)
+    }
+
+    /// 第 line 行(从1开始)及其前后各 context 行的源码片段, 行号超出文件范围时返回 None
+    pub fn snippet(&self, line: u64, context: u64) -> Option<Snippet> {
+        let lines: Vec<&str> = self.content.lines().collect();
+        if line == 0 || line > lines.len() as u64 {
+            return None;
+        }
+        let start_line = line.saturating_sub(context).max(1);
+        let end_line = line.saturating_add(context).min(lines.len() as u64);
+        Some(Snippet {
+            start_line,
+            end_line,
+            code: lines[start_line as usize - 1..end_line as usize].join("\n"),
+        })
+    }
+}
+
+/// 规则 wiki 接口(queryWikiByLanguageErrorid)的响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WikiResponse {
+    pub wiki_description: Option<String>,
+    pub wiki_detail: Option<String>,
+    pub wiki_example: Option<String>,
+}
+
+/// 缺陷的解决方案
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Solution {
+    pub wiki_description: String,
+    #[serde(default)]
+    pub wiki_detail: String,
+    #[serde(default)]
+    pub wiki_example: String,
+}
+
+impl WikiResponse {
+    /// 没有 wiki_description 时视为无解决方案
+    pub fn into_solution(self) -> Option<Solution> {
+        Some(Solution {
+            wiki_description: self.wiki_description?,
+            wiki_detail: self.wiki_detail.unwrap_or_default(),
+            wiki_example: self.wiki_example.unwrap_or_default(),
+        })
+    }
+}
+
+// CVE 编号可能是数组或以逗号分隔的字符串
+fn lenient_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum List {
+        Items(Vec<String>),
+        Text(String),
+    }
+    Ok(match Option::<List>::deserialize(deserializer)? {
+        Some(List::Items(items)) => items,
+        Some(List::Text(text)) => text
+            .split([',', ';'])
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_owned)
+            .collect(),
+        None => Vec::new(),
+    })
+}
+
+/// 规则列表接口(queryRuleByLanguage)的响应
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleList {
+    #[serde(default)]
+    pub status: Option<u64>,
+    #[serde(default)]
+    pub error_msg: Option<String>,
+    #[serde(default, alias = "rule", alias = "ruleList")]
+    pub rules: Vec<Rule>,
+}
+
+/// 语言的一条规则, 未建模的字段原样保留在 extra 中
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    #[serde(alias = "errorid", alias = "errorId")]
+    pub error_code: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 开源组件检测结果接口(queryTaskJarsDetectionResult)的响应, 统计项原样保留
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentResult {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+    #[serde(default, alias = "jars", alias = "jarList")]
+    pub components: Vec<Component>,
+    #[serde(default)]
+    pub statistics: Map<String, Value>,
+}
+
+/// 检测到的开源组件, 未建模的字段原样保留在 extra 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Component {
+    #[serde(alias = "jarName")]
+    pub name: String,
+    #[serde(default, alias = "jarVersion", skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// 组件存在的漏洞编号
+    #[serde(
+        default,
+        alias = "cveList",
+        alias = "cve",
+        deserialize_with = "lenient_list",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub cves: Vec<String>,
+    #[serde(default, alias = "licenses", skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity_level: Option<i64>,
+    /// 组件所在的文件, eg. lib/commons-collections-3.2.1.jar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 文件内容接口(getFile)的响应, 文件内容为字节数组
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileContent {
+    pub byte_array_of_files: Vec<i16>,
+}
+
+/// 测试用的缺陷, fields 中的字段覆盖默认的 errorCode、filePath、line
+#[cfg(test)]
+pub(crate) fn test_problem(fields: Value) -> Problem {
+    let mut problem = Map::new();
+    problem.insert("errorCode".to_owned(), "NPE".into());
+    problem.insert("filePath".to_owned(), "src/A.java".into());
+    problem.insert("line".to_owned(), 4.into());
+    if let Value::Object(fields) = fields {
+        problem.extend(fields);
+    }
+    serde_json::from_value(Value::Object(problem)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn problem() -> Problem {
+        test_problem(json!({
+            "filePath": "src\\A.java",
+            "traceBlock": [
+                { "file": "src/A.java", "line": 3, "message": "s = null" },
+                { "file": "src/A.java", "line": 4, "message": "deref" }
+            ]
+        }))
+    }
+
+    const CODE_LINE: &str = "s.length();";
+
+    #[test]
+    fn fingerprint_ignores_source_snippet() {
+        let without_source = problem();
+        let mut with_source = problem();
+        with_source.snippet = Some(Snippet {
+            start_line: 3,
+            end_line: 4,
+            code: "String s = null;\ns.length();".to_owned(),
+        });
+        assert_eq!(
+            without_source.compute_fingerprint(Some(CODE_LINE)),
+            with_source.compute_fingerprint(Some(CODE_LINE))
+        );
+    }
+
+    #[test]
+    fn fingerprint_ignores_line_and_whitespace() {
+        let original = problem();
+        let mut moved = problem();
+        moved.line = Some(40);
+        moved.file_path = Some("src/A.java".to_owned());
+        moved.trace_block[0].line = Some(39);
+        moved.trace_block[0].message = Some("s  =  null".to_owned());
+        assert_eq!(
+            original.compute_fingerprint(Some(CODE_LINE)),
+            moved.compute_fingerprint(Some("    s.length( );"))
+        );
+    }
+
+    #[test]
+    fn fingerprint_depends_on_rule_path_and_code_line() {
+        let original = problem();
+        let mut other_rule = problem();
+        other_rule.error_code = "SQLI".to_owned();
+        let mut other_file = problem();
+        other_file.file_path = Some("src/B.java".to_owned());
+        let fingerprint = original.compute_fingerprint(Some(CODE_LINE));
+        assert_ne!(fingerprint, other_rule.compute_fingerprint(Some(CODE_LINE)));
+        assert_ne!(fingerprint, other_file.compute_fingerprint(Some(CODE_LINE)));
+        // 同一文件中相同规则、没有跟踪步骤的缺陷按所在行的源码区分
+        assert_ne!(
+            fingerprint,
+            original.compute_fingerprint(Some("t.length();"))
+        );
+        assert_ne!(fingerprint, original.compute_fingerprint(None));
+    }
+
+    #[test]
+    fn rule_list_accepts_field_aliases() {
+        let rules: RuleList = serde_json::from_value(json!({
+            "status": 0,
+            "ruleList": [{ "errorid": "NPE" }, { "errorCode": "SQLI", "name": "SQL注入" }]
+        }))
+        .unwrap();
+        let codes: Vec<&str> = rules.rules.iter().map(|r| r.error_code.as_str()).collect();
+        assert_eq!(codes, ["NPE", "SQLI"]);
+    }
+
+    #[test]
+    fn source_file_line() {
+        let file = SourceFile::decode(&"a\r\nb\nc".bytes().map(i16::from).collect::<Vec<_>>());
+        assert_eq!(file.line(1), Some("a"));
+        assert_eq!(file.line(3), Some("c"));
+        assert_eq!(file.line(0), None);
+        assert_eq!(file.line(4), None);
+    }
+
+    // getFile 接口按 Java 的有符号字节返回文件内容
+    fn java_bytes(bytes: &[u8]) -> Vec<i16> {
+        bytes.iter().map(|b| i16::from(*b as i8)).collect()
+    }
+
+    #[test]
+    fn decode_detects_encoding() {
+        let file = SourceFile::decode(&java_bytes("\u{feff}中文".as_bytes()));
+        assert_eq!(
+            (file.encoding.as_str(), file.content.as_str()),
+            ("UTF-8", "中文")
+        );
+
+        let (gbk, _, _) = encoding_rs::GBK.encode("// 中文注释");
+        let file = SourceFile::decode(&java_bytes(&gbk));
+        assert_eq!(
+            (file.encoding.as_str(), file.content.as_str()),
+            ("GBK", "// 中文注释")
+        );
+
+        // GBK 中没有的字符按 GB18030 的四字节编码
+        let (gb18030, _, _) = encoding_rs::GB18030.encode("中文 å");
+        let file = SourceFile::decode(&java_bytes(&gb18030));
+        assert_eq!(
+            (file.encoding.as_str(), file.content.as_str()),
+            ("GB18030", "中文 å")
+        );
+
+        let file = SourceFile::decode(&java_bytes(b"a\xff\xffb"));
+        assert_eq!(file.encoding, "unknown");
+        assert!(file.content.starts_with('a') && file.content.ends_with('b'));
+    }
+
+    #[test]
+    fn source_file_snippet() {
+        let file = SourceFile::decode(&java_bytes(b"1\n2\n3\n4\n5"));
+        let snippet = file.snippet(3, 1).unwrap();
+        assert_eq!((snippet.start_line, snippet.end_line), (2, 4));
+        assert_eq!(snippet.code, "2\n3\n4");
+        assert_eq!(snippet.line(3), Some("3"));
+        assert_eq!(snippet.line(1), None);
+
+        // 上下文在文件开头及结尾处截断
+        let snippet = file.snippet(1, 2).unwrap();
+        assert_eq!((snippet.start_line, snippet.end_line), (1, 3));
+        let snippet = file.snippet(5, 2).unwrap();
+        assert_eq!((snippet.start_line, snippet.end_line), (3, 5));
+        assert_eq!(file.snippet(5, 0).unwrap().code, "5");
+        assert_eq!(file.snippet(5, u64::MAX).unwrap().start_line, 1);
+
+        assert!(file.snippet(0, 1).is_none());
+        assert!(file.snippet(6, 1).is_none());
+    }
+}
@@ -0,0 +1,346 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use tempfile::NamedTempFile;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{error::CodepeckerError, progress::human_size};
+
+/// 打包时任意层级都跳过的版本库目录
+const VCS_DIRS: [&str; 2] = [".git", ".svn"];
+/// 构建产物目录及标识构建根目录的文件, 只在源码目录顶层或构建根目录下跳过,
+/// 避免跳过 com/x/target/ 这样的源码包
+const BUILD_DIRS: [(&str, &[&str]); 2] = [
+    ("target", &["Cargo.toml", "pom.xml", "build.sbt"]),
+    ("node_modules", &["package.json"]),
+];
+
+/// 打包源码目录的参数
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PackageOptions {
+    /// 只打包匹配的文件, 为空时打包全部文件
+    pub(crate) include: Vec<String>,
+    /// 不打包匹配的文件
+    pub(crate) exclude: Vec<String>,
+    /// 只打包git跟踪的文件
+    pub(crate) git_tracked: bool,
+    /// 压缩包大小的上限, 超过时停止打包
+    pub(crate) max_size: Option<u64>,
+}
+
+/// 文件大小, eg. 500MB, 2GB, 1048576
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ByteSize(pub(crate) u64);
+
+impl FromStr for ByteSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("文件大小 {s} 的格式应为 数字+单位, eg. 500MB"))?;
+        let unit: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "K" | "KB" => 1 << 10,
+            "M" | "MB" => 1 << 20,
+            "G" | "GB" => 1 << 30,
+            "T" | "TB" => 1 << 40,
+            other => return Err(format!("未知的文件大小单位 {other}, 可选值: B,KB,MB,GB,TB")),
+        };
+        Ok(Self((number * unit as f64) as u64))
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", human_size(self.0))
+    }
+}
+
+impl Serialize for ByteSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// 配置文件中可以是字符串或字节数, eg. max_archive_size = "500MB"
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Bytes(u64),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Bytes(bytes) => Ok(Self(bytes)),
+            Raw::Text(text) => text.parse().map_err(de::Error::custom),
+        }
+    }
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet, CodepeckerError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| {
+            CodepeckerError::InvalidConfig(format!("文件匹配规则{pattern}不合法: {e}"))
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| CodepeckerError::InvalidConfig(e.to_string()))
+}
+
+// 通过 git ls-files 获取git跟踪的文件
+fn git_tracked_files(dir: &Path) -> Result<HashSet<PathBuf>, CodepeckerError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["ls-files", "-z"])
+        .output()?;
+    if !output.status.success() {
+        return Err(CodepeckerError::CustomInvalidInfo(format!(
+            "获取git跟踪的文件失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output
+        .stdout
+        .split(|b| *b == 0)
+        .filter(|path| !path.is_empty())
+        .map(|path| PathBuf::from(String::from_utf8_lossy(path).into_owned()))
+        .collect())
+}
+
+// 目录是否跳过: 版本库目录总是跳过, 构建产物目录只在顶层(depth 为1)或构建根目录下跳过
+fn is_skipped_dir(path: &Path, depth: usize) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    if VCS_DIRS.iter().any(|dir| name == *dir) {
+        return true;
+    }
+    BUILD_DIRS
+        .iter()
+        .find(|(dir, _)| name == *dir)
+        .is_some_and(|(_, manifests)| {
+            depth == 1
+                || path
+                    .parent()
+                    .is_some_and(|root| manifests.iter().any(|m| root.join(m).is_file()))
+        })
+}
+
+// 收集需要打包的文件, 遵循 .gitignore 并跳过构建产物
+fn collect_files(dir: &Path, options: &PackageOptions) -> Result<Vec<PathBuf>, CodepeckerError> {
+    let include = glob_set(&options.include)?;
+    let exclude = glob_set(&options.exclude)?;
+    let tracked = if options.git_tracked {
+        Some(git_tracked_files(dir)?)
+    } else {
+        None
+    };
+
+    let walker = WalkBuilder::new(dir)
+        .hidden(false)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| {
+            !(entry.file_type().is_some_and(|t| t.is_dir())
+                && is_skipped_dir(entry.path(), entry.depth()))
+        })
+        .build();
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(|e| CodepeckerError::CustomInvalidInfo(e.to_string()))?;
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+        if (!options.include.is_empty() && !include.is_match(relative))
+            || exclude.is_match(relative)
+            || tracked.as_ref().is_some_and(|t| !t.contains(relative))
+        {
+            continue;
+        }
+        files.push(relative.to_path_buf());
+    }
+    Ok(files)
+}
+
+/// 将源码目录打包为zip临时文件, 临时文件离开作用域后删除
+pub(crate) fn package(
+    dir: &Path,
+    options: &PackageOptions,
+) -> Result<NamedTempFile, CodepeckerError> {
+    if !dir.is_dir() {
+        return Err(CodepeckerError::CustomInvalidInfo(format!(
+            "源码目录{}不存在",
+            dir.display()
+        )));
+    }
+    let files = collect_files(dir, options)?;
+    if files.is_empty() {
+        return Err(CodepeckerError::CustomInvalidInfo(format!(
+            "源码目录{}中没有需要打包的文件",
+            dir.display()
+        )));
+    }
+
+    let name = dir
+        .canonicalize()?
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "source".to_owned());
+    let file = tempfile::Builder::new()
+        .prefix(&format!("{name}-"))
+        .suffix(".zip")
+        .tempfile()?;
+    let mut zip = ZipWriter::new(file.reopen()?);
+    let zip_options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+    for relative in &files {
+        log::debug!("打包文件: {}", relative.display());
+        let entry_name = relative.to_string_lossy().replace('\\', "/");
+        zip.start_file(entry_name, zip_options)
+            .map_err(|e| CodepeckerError::IoError(io::Error::other(e)))?;
+        io::copy(&mut File::open(dir.join(relative))?, &mut zip)?;
+        // 每写入一个文件检查一次已写入的大小, 超过上限时不再打包剩余的文件
+        if let Some(limit) = options.max_size {
+            let size = file.as_file().metadata()?.len();
+            if size > limit {
+                return Err(CodepeckerError::ArchiveTooLarge {
+                    path: dir.display().to_string(),
+                    size,
+                    limit,
+                });
+            }
+        }
+    }
+    zip.finish()
+        .map_err(|e| CodepeckerError::IoError(io::Error::other(e)))?;
+
+    let size = file.as_file().metadata()?.len();
+    log::info!(
+        "打包源码目录{}完成: {}个文件, 压缩包大小{}",
+        dir.display(),
+        files.len(),
+        human_size(size)
+    );
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn touch(root: &Path, path: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "x").unwrap();
+    }
+
+    #[test]
+    fn parse_byte_size() {
+        for (text, bytes) in [
+            ("1048576", 1 << 20),
+            ("500MB", 500 << 20),
+            ("2 gb", 2 << 30),
+            ("1.5K", 1536),
+            ("10b", 10),
+        ] {
+            assert_eq!(text.parse::<ByteSize>().unwrap().0, bytes, "{text}");
+        }
+        for text in ["", "MB", "10PB", "1..5MB"] {
+            assert!(text.parse::<ByteSize>().is_err(), "{text}");
+        }
+        assert_eq!(ByteSize(500 << 20).to_string(), human_size(500 << 20));
+        // 配置文件中可以是字节数或字符串
+        let size: ByteSize = serde_json::from_str("1024").unwrap();
+        assert_eq!(size.0, 1024);
+        let size: ByteSize = serde_json::from_str("\"1KB\"").unwrap();
+        assert_eq!(size.0, 1024);
+    }
+
+    #[test]
+    fn package_stops_when_archive_too_large() {
+        let dir = tempfile::tempdir().unwrap();
+        // 随机内容无法压缩, 每个文件约占64KB
+        let mut state = 1u64;
+        for i in 0..8 {
+            let bytes: Vec<u8> = (0..64 << 10)
+                .map(|_| {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    (state >> 56) as u8
+                })
+                .collect();
+            fs::write(dir.path().join(format!("{i}.bin")), bytes).unwrap();
+        }
+        let options = PackageOptions {
+            max_size: Some(100 << 10),
+            ..Default::default()
+        };
+        match package(dir.path(), &options) {
+            Err(CodepeckerError::ArchiveTooLarge { size, limit, .. }) => {
+                assert_eq!(limit, 100 << 10);
+                assert!(size > limit && size < 256 << 10, "{size}");
+            }
+            other => panic!("{other:?}"),
+        }
+
+        let options = PackageOptions {
+            max_size: Some(1 << 20),
+            ..Default::default()
+        };
+        assert!(package(dir.path(), &options).is_ok());
+    }
+
+    #[test]
+    fn skips_build_dirs_only_at_build_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "target/debug/a.o");
+        touch(root, "node_modules/x/index.js");
+        touch(root, "web/package.json");
+        touch(root, "web/node_modules/y/index.js");
+        touch(root, "src/com/x/target/Target.java");
+        touch(root, "src/lib/node_modules/z.js");
+        touch(root, "src/.git/HEAD");
+
+        let files = collect_files(root, &PackageOptions::default()).unwrap();
+        let files: Vec<String> = files
+            .iter()
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                "src/com/x/target/Target.java",
+                "src/lib/node_modules/z.js",
+                "web/package.json"
+            ]
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::{Duration, Instant};
+
+/// 输出上传进度的最小间隔
+const REPORT_INTERVAL: Duration = Duration::from_secs(2);
+
+// 以可读的单位输出字节数
+pub(crate) fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes}{}", UNITS[0])
+    } else {
+        format!("{size:.2}{}", UNITS[unit])
+    }
+}
+
+/// 上传进度, 在日志中定期输出已发送字节数、速率及预计剩余时间
+pub(crate) struct UploadProgress {
+    total: u64,
+    sent: u64,
+    start: Instant,
+    last_report: Instant,
+}
+
+impl UploadProgress {
+    pub(crate) fn new(total: u64) -> Self {
+        let now = Instant::now();
+        Self {
+            total,
+            sent: 0,
+            start: now,
+            last_report: now,
+        }
+    }
+
+    // 记录新发送的字节数, 距上次输出超过间隔或发送完成时输出进度
+    pub(crate) fn advance(&mut self, bytes: u64) {
+        self.sent += bytes;
+        if self.sent >= self.total || self.last_report.elapsed() >= REPORT_INTERVAL {
+            self.last_report = Instant::now();
+            self.report();
+        }
+    }
+
+    fn report(&self) {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let rate = if elapsed > 0.0 {
+            self.sent as f64 / elapsed
+        } else {
+            0.0
+        };
+        let percent = if self.total > 0 {
+            self.sent as f64 * 100.0 / self.total as f64
+        } else {
+            100.0
+        };
+        let remaining = self.total.saturating_sub(self.sent);
+        let eta = if remaining == 0 {
+            "0s".to_owned()
+        } else if rate > 0.0 {
+            format!("{:.0}s", remaining as f64 / rate)
+        } else {
+            "未知".to_owned()
+        };
+        log::info!(
+            "上传进度: {}/{} ({percent:.1}%), 速率 {}/s, 预计剩余 {eta}",
+            human_size(self.sent),
+            human_size(self.total),
+            human_size(rate as u64),
+        );
+    }
+}
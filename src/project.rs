@@ -1,17 +1,41 @@
+use std::fmt;
+
+/// 检测项目的配置
 #[derive(Debug, Clone)]
-pub(crate) struct Project {
-    pub(crate) name: String,
-    pub(crate) lang: String,
-    pub(crate) template: String,
-    pub(crate) group: Option<String>,
-    pub(crate) rule: Option<String>,
+pub struct Project {
+    /// 项目名称
+    pub name: String,
+    /// 项目语言, eg. java
+    pub lang: String,
+    /// 缺陷模板类型: default, high, user_defined
+    pub template: String,
+    /// 项目组id
+    pub group: Option<String>,
+    /// 缺陷模板规则, 当缺陷模板类型为 user_defined 时生效
+    pub rule: Option<String>,
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct Source<T> {
-    pub(crate) remote: String,
-    pub(crate) url: T,
-    pub(crate) user: String,
-    pub(crate) password: String,
-    pub(crate) branch: Option<String>,
+/// SVN/GIT 源码仓库, 用户名及密码为空时服务端按公开仓库匿名拉取
+#[derive(Clone)]
+pub struct Source<T> {
+    /// 下载方式: 1 为SVN, 2 为GIT
+    pub remote: String,
+    pub url: T,
+    pub user: String,
+    /// 密码或访问令牌
+    pub password: String,
+    pub branch: Option<String>,
+}
+
+// 密码不输出到日志中
+impl<T: fmt::Debug> fmt::Debug for Source<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Source")
+            .field("remote", &self.remote)
+            .field("url", &self.url)
+            .field("user", &self.user)
+            .field("password", &"******")
+            .field("branch", &self.branch)
+            .finish()
+    }
 }
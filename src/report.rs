@@ -0,0 +1,276 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::Path,
+    str::FromStr,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    args::FORMATS,
+    error::CodepeckerError,
+    gate::QualityGate,
+    model::{BaselineState, Component, ComponentResult, Problem, SourceFile, Statistics},
+    severity::{Severity, SeverityFilter, SeverityMap},
+};
+
+mod cyclonedx;
+mod gitlab;
+mod html;
+mod junit;
+mod markdown;
+mod sarif;
+
+pub(crate) use markdown::SummaryOptions;
+
+/// 报告的格式及输出路径, eg. sarif:scan.sarif
+#[derive(Debug, Clone)]
+pub(crate) struct ReportTarget {
+    pub(crate) format: String,
+    pub(crate) path: String,
+}
+
+impl FromStr for ReportTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, path) = s
+            .split_once(':')
+            .ok_or_else(|| format!("报告 {s} 的格式应为 格式:路径, eg. sarif:scan.sarif"))?;
+        let format = FORMATS
+            .into_iter()
+            .find(|f| f.eq_ignore_ascii_case(format.trim()))
+            .ok_or_else(|| format!("未知的报告格式 {format}, 可选值: {}", FORMATS.join(",")))?;
+        if path.trim().is_empty() {
+            return Err(format!("报告 {s} 缺少输出路径"));
+        }
+        Ok(Self {
+            format: format.to_owned(),
+            path: path.trim().to_owned(),
+        })
+    }
+}
+
+impl fmt::Display for ReportTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.format, self.path)
+    }
+}
+
+impl Serialize for ReportTarget {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// 配置文件中与命令行参数的格式相同, eg. report = ["json:results.json", "sarif:scan.sarif"]
+impl<'de> Deserialize<'de> for ReportTarget {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// 一次获取到的检测结果, 供各格式的报告使用
+#[derive(Debug, Clone)]
+pub(crate) struct TaskResult {
+    pub(crate) task_id: String,
+    pub(crate) severity: SeverityFilter,
+    pub(crate) info: Statistics,
+    pub(crate) problems: Vec<Problem>,
+    /// 与基线对比时, 基线中存在而本次已修复的缺陷
+    pub(crate) fixed: Option<Vec<Problem>>,
+    /// 缺陷涉及的源文件, 按路径去重, 只在获取源文件时存在
+    pub(crate) files: BTreeMap<String, SourceFile>,
+    /// 质量门禁, 供 junit 等格式判断缺陷是否视为失败
+    pub(crate) gate: Option<QualityGate>,
+    /// 开源组件检测结果, 只在获取开源组件时存在
+    pub(crate) components: Option<ComponentResult>,
+    /// 开源组件检测结果中的全部组件, 不按级别筛选, 供 SBOM 使用
+    pub(crate) all_components: Vec<Component>,
+    /// severityLevel 与级别的对应关系
+    pub(crate) severity_map: SeverityMap,
+}
+
+impl TaskResult {
+    // severityLevel 对应的级别
+    pub(crate) fn severity(&self, severity_level: Option<i64>) -> Severity {
+        self.severity_map.severity(severity_level)
+    }
+
+    // 质量门禁关注的缺陷: 不包括被忽略的缺陷, 与基线对比时只关注新增的缺陷
+    pub(crate) fn gated_problems(&self) -> impl Iterator<Item = &Problem> {
+        self.problems.iter().filter(|p| {
+            p.suppression.is_none() && p.baseline_state != Some(BaselineState::Unchanged)
+        })
+    }
+}
+
+// 转义 HTML/XML 特殊字符, 服务端返回的内容一律按文本输出. XML 1.0 不允许的控制字符直接去除
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\t' | '\n' | '\r' => escaped.push(c),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => {}
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 将扫描结果按指定格式写入文件
+pub(crate) fn write(
+    format: &str,
+    output: &str,
+    result: &TaskResult,
+    summary: &SummaryOptions<'_>,
+) -> Result<(), CodepeckerError> {
+    if let Some(text) = match format {
+        "html" => Some(html::build(result)),
+        "markdown" => Some(markdown::build(result, summary)),
+        "junit" => Some(junit::build(result)),
+        _ => None,
+    } {
+        fs::write(output, text)?;
+        log::info!("将{format}格式的扫描结果写入文件{:?}完成!", output);
+        return Ok(());
+    }
+    let result_json = match format {
+        "sarif" => sarif::build(result),
+        "gitlab-codequality" => gitlab::code_quality(result),
+        "gitlab-sast" => gitlab::sast(result),
+        "cyclonedx" => cyclonedx::build(result),
+        _ => {
+            let mut result_json = serde_json::json!({
+                "task_id": result.task_id,
+                "severity": result.severity,
+                "problem_count": result.problems.len(),
+                "suppressed_count": result.problems.iter().filter(|p| p.suppression.is_some()).count(),
+                "info": result.info,
+                "problems": result.problems
+            });
+            if let Some(fixed) = &result.fixed {
+                let new = result
+                    .problems
+                    .iter()
+                    .filter(|p| p.baseline_state == Some(BaselineState::New))
+                    .count();
+                result_json["baseline"] = serde_json::json!({
+                    "new": new,
+                    "unchanged": result.problems.len() - new,
+                    "fixed": fixed
+                });
+            }
+            if let Some(components) = &result.components {
+                result_json["components"] = serde_json::json!({
+                    "component_count": components.components.len(),
+                    "statistics": components.statistics,
+                    "components": components.components
+                });
+            }
+            if !result.files.is_empty() {
+                result_json["files"] = serde_json::json!(result.files);
+            }
+            result_json
+        }
+    };
+    // 将 JSON 写入文件
+    let file = File::create(output)?;
+    log::debug!("{:?}", file.metadata());
+    serde_json::to_writer_pretty(file, &result_json)?;
+    log::info!("将{format}格式的扫描结果写入文件{:?}完成!", output);
+    Ok(())
+}
+
+// 追加写入 Markdown 摘要, 便于写入 $GITHUB_STEP_SUMMARY 等已有内容的文件
+pub(crate) fn write_summary(
+    path: &Path,
+    result: &TaskResult,
+    summary: &SummaryOptions<'_>,
+) -> Result<(), CodepeckerError> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(markdown::build(result, summary).as_bytes())?;
+    log::info!("将Markdown摘要写入文件{:?}完成!", path);
+    Ok(())
+}
+
+/// 测试用的检测结果, 只包含给定的缺陷
+#[cfg(test)]
+pub(crate) fn test_result(problems: Vec<Problem>) -> TaskResult {
+    TaskResult {
+        task_id: "1".to_owned(),
+        severity: SeverityFilter::default(),
+        info: Statistics::default(),
+        problems,
+        fixed: None,
+        files: BTreeMap::new(),
+        gate: None,
+        components: None,
+        all_components: Vec::new(),
+        severity_map: SeverityMap::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_report_target() {
+        let target: ReportTarget = " SARIF : out/scan.sarif ".parse().unwrap();
+        assert_eq!(
+            (target.format.as_str(), target.path.as_str()),
+            ("sarif", "out/scan.sarif")
+        );
+        assert_eq!(target.to_string(), "sarif:out/scan.sarif");
+        // 只按第一个:分隔, 路径中可以包含:
+        let target: ReportTarget = "json:C:\\out\\r.json".parse().unwrap();
+        assert_eq!(target.path, "C:\\out\\r.json");
+        for text in ["sarif", "pdf:r.pdf", "json: "] {
+            assert!(text.parse::<ReportTarget>().is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn write_json_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.json");
+        let result = test_result(vec![crate::model::test_problem(serde_json::json!({}))]);
+        let summary = SummaryOptions {
+            top: 10,
+            limit: 65000,
+            repo_url: None,
+            commit_sha: None,
+        };
+        write("json", path.to_str().unwrap(), &result, &summary).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["task_id"], "1");
+        assert_eq!(json["problem_count"], 1);
+        assert_eq!(json["problems"][0]["errorCode"], "NPE");
+        assert!(json.get("baseline").is_none());
+        assert!(json.get("files").is_none());
+    }
+
+    #[test]
+    fn escape_strips_characters_invalid_in_xml() {
+        assert_eq!(
+            escape("a<b>&\"'\u{0}\u{8}\u{1b}\u{fffe}\tc\r\nd"),
+            "a&lt;b&gt;&amp;&quot;&#39;\tc\r\nd"
+        );
+        assert_eq!(escape("中文 ✓"), "中文 ✓");
+    }
+}
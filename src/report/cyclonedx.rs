@@ -0,0 +1,295 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use super::TaskResult;
+use crate::{model::Component, severity::Severity};
+
+const SPEC_VERSION: &str = "1.5";
+
+// 常见的 SPDX 许可证标识, 其余许可证按名称输出
+const SPDX_IDS: [&str; 16] = [
+    "Apache-2.0",
+    "MIT",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "GPL-2.0-only",
+    "GPL-3.0-only",
+    "LGPL-2.1-only",
+    "LGPL-3.0-only",
+    "MPL-2.0",
+    "EPL-1.0",
+    "EPL-2.0",
+    "ISC",
+    "CDDL-1.0",
+    "Unlicense",
+    "CC0-1.0",
+    "AGPL-3.0-only",
+];
+
+// package url 中的各部分需要百分号编码
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'-' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn extra_str<'a>(component: &'a Component, key: &str) -> Option<&'a str> {
+    component
+        .extra
+        .get(key)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+}
+
+// 组件的 package url: 优先使用服务端返回的 purl, 有 groupId 或名称为 group:artifact 时为 maven, 否则为 generic
+fn purl(component: &Component) -> String {
+    if let Some(purl) = extra_str(component, "purl") {
+        return purl.to_owned();
+    }
+    let (namespace, name) = match extra_str(component, "groupId") {
+        Some(group) => (Some(group), component.name.as_str()),
+        None => match component.name.split_once(':') {
+            Some((group, artifact)) => (Some(group), artifact),
+            None => (None, component.name.as_str()),
+        },
+    };
+    let mut purl = match namespace {
+        Some(namespace) => format!("pkg:maven/{}/{}", encode(namespace), encode(name)),
+        None => format!("pkg:generic/{}", encode(name)),
+    };
+    if let Some(version) = &component.version {
+        purl.push('@');
+        purl.push_str(&encode(version));
+    }
+    purl
+}
+
+fn license(name: &str) -> Value {
+    match SPDX_IDS
+        .into_iter()
+        .find(|id| id.eq_ignore_ascii_case(name.trim()))
+    {
+        Some(id) => json!({ "license": { "id": id } }),
+        None => json!({ "license": { "name": name.trim() } }),
+    }
+}
+
+// 由任务id及生成时间得到 RFC 4122 格式的序列号
+fn serial_number(task_id: &str, timestamp: &str) -> String {
+    let digest = Sha256::digest(format!("{task_id}\0{timestamp}").as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!(
+        "urn:uuid:{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+// 将开源组件检测结果转换为 CycloneDX JSON 格式的 SBOM, 相同的 CVE 合并为一个漏洞
+pub(crate) fn build(result: &TaskResult) -> Value {
+    let timestamp = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    // SBOM 需要列出全部组件, 不受 --component-severity 影响
+    let components = &result.all_components;
+
+    let mut bom_components = Vec::with_capacity(components.len());
+    // bom-ref 必须唯一, 相同 purl 的组件只输出一次
+    let mut refs = BTreeSet::new();
+    // CVE -> (最高级别, 受影响组件的 bom-ref)
+    let mut vulnerabilities: BTreeMap<&str, (Severity, Vec<String>)> = BTreeMap::new();
+    for component in components {
+        let purl = purl(component);
+        for cve in &component.cves {
+            let (severity, affects) = vulnerabilities
+                .entry(cve)
+                .or_insert((Severity::Unknown, Vec::new()));
+            *severity = (*severity).max(result.severity(component.severity_level));
+            if !affects.contains(&purl) {
+                affects.push(purl.clone());
+            }
+        }
+        if !refs.insert(purl.clone()) {
+            continue;
+        }
+        let mut bom_component = json!({
+            "type": "library",
+            "bom-ref": purl,
+            "name": component.name,
+            "purl": purl
+        });
+        if let Some(version) = &component.version {
+            bom_component["version"] = json!(version);
+        }
+        if let Some(name) = &component.license {
+            bom_component["licenses"] = json!([license(name)]);
+        }
+        if let Some(path) = &component.file_path {
+            bom_component["properties"] = json!([{ "name": "codepecker:filePath", "value": path }]);
+        }
+        bom_components.push(bom_component);
+    }
+
+    let vulnerabilities: Vec<Value> = vulnerabilities
+        .into_iter()
+        .map(|(cve, (severity, affects))| {
+            let mut vulnerability = json!({
+                "id": cve,
+                "ratings": [{ "severity": severity.name(), "method": "other" }],
+                "affects": affects.into_iter().map(|r| json!({ "ref": r })).collect::<Vec<_>>()
+            });
+            if cve.starts_with("CVE-") {
+                vulnerability["source"] = json!({
+                    "name": "NVD",
+                    "url": format!("https://nvd.nist.gov/vuln/detail/{cve}")
+                });
+            }
+            vulnerability
+        })
+        .collect();
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": SPEC_VERSION,
+        "serialNumber": serial_number(&result.task_id, &timestamp),
+        "version": 1,
+        "metadata": {
+            "timestamp": timestamp,
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": "codepecker",
+                    "version": env!("CARGO_PKG_VERSION")
+                }]
+            },
+            "properties": [{ "name": "codepecker:taskId", "value": result.task_id }]
+        },
+        "components": bom_components,
+        "vulnerabilities": vulnerabilities
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::test_result;
+
+    fn component(fields: Value) -> Component {
+        serde_json::from_value(fields).unwrap()
+    }
+
+    #[test]
+    fn purl_by_group_or_name() {
+        let purls: Vec<String> = [
+            json!({ "name": "x", "purl": "pkg:npm/x@1.0.0" }),
+            json!({ "name": "commons-text", "groupId": "org.apache.commons", "version": "1.9" }),
+            json!({ "name": "org.yaml:snakeyaml", "version": "1.33" }),
+            json!({ "name": "my lib", "version": "1.0+build" }),
+        ]
+        .into_iter()
+        .map(|fields| purl(&component(fields)))
+        .collect();
+        assert_eq!(
+            purls,
+            [
+                "pkg:npm/x@1.0.0",
+                "pkg:maven/org.apache.commons/commons-text@1.9",
+                "pkg:maven/org.yaml/snakeyaml@1.33",
+                "pkg:generic/my%20lib@1.0%2Bbuild"
+            ]
+        );
+    }
+
+    #[test]
+    fn components_and_vulnerabilities() {
+        let mut result = test_result(Vec::new());
+        result.all_components = vec![
+            component(json!({
+                "jarName": "org.yaml:snakeyaml",
+                "jarVersion": "1.33",
+                "cveList": ["CVE-2022-1471"],
+                "licenses": "apache-2.0",
+                "severityLevel": 2,
+                "filePath": "lib/snakeyaml-1.33.jar"
+            })),
+            // 相同 purl 的组件只输出一次
+            component(json!({
+                "name": "org.yaml:snakeyaml",
+                "version": "1.33",
+                "cves": ["CVE-2022-1471"],
+                "severityLevel": 1
+            })),
+            component(json!({
+                "name": "fastjson",
+                "cves": ["CVE-2022-1471", "XRAY-1"],
+                "license": "Custom License",
+                "severityLevel": 3
+            })),
+        ];
+        let bom = build(&result);
+        let components = bom["components"].as_array().unwrap();
+        assert_eq!(components.len(), 2);
+        assert_eq!(
+            components[0]["bom-ref"],
+            "pkg:maven/org.yaml/snakeyaml@1.33"
+        );
+        assert_eq!(components[0]["licenses"][0]["license"]["id"], "Apache-2.0");
+        assert_eq!(
+            components[0]["properties"][0]["value"],
+            "lib/snakeyaml-1.33.jar"
+        );
+        assert_eq!(
+            components[1]["licenses"][0]["license"]["name"],
+            "Custom License"
+        );
+        assert!(components[1].get("version").is_none());
+
+        let vulnerabilities = bom["vulnerabilities"].as_array().unwrap();
+        assert_eq!(vulnerabilities.len(), 2);
+        let cve = &vulnerabilities[0];
+        assert_eq!(cve["id"], "CVE-2022-1471");
+        // 取受影响组件中最高的级别
+        assert_eq!(cve["ratings"][0]["severity"], "critical");
+        assert_eq!(
+            cve["affects"],
+            json!([
+                { "ref": "pkg:maven/org.yaml/snakeyaml@1.33" },
+                { "ref": "pkg:generic/fastjson" }
+            ])
+        );
+        assert_eq!(cve["source"]["name"], "NVD");
+        assert_eq!(vulnerabilities[1]["id"], "XRAY-1");
+        assert!(vulnerabilities[1].get("source").is_none());
+    }
+
+    #[test]
+    fn serial_number_is_uuid_v4_format() {
+        let serial = serial_number("T1", "2024-01-01T00:00:00Z");
+        assert_eq!(serial, serial_number("T1", "2024-01-01T00:00:00Z"));
+        assert_ne!(serial, serial_number("T2", "2024-01-01T00:00:00Z"));
+        let uuid = serial.strip_prefix("urn:uuid:").unwrap();
+        let parts: Vec<&str> = uuid.split('-').collect();
+        assert_eq!(
+            parts.iter().map(|p| p.len()).collect::<Vec<_>>(),
+            [8, 4, 4, 4, 12]
+        );
+        assert!(parts[2].starts_with('4'));
+        assert!(matches!(
+            parts[3].chars().next(),
+            Some('8' | '9' | 'a' | 'b')
+        ));
+    }
+}
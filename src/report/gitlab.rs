@@ -0,0 +1,213 @@
+use serde_json::{json, Value};
+
+use super::TaskResult;
+use crate::{model::Problem, severity::Severity};
+
+// GitLab 安全报告的 schema 版本
+const SAST_SCHEMA_VERSION: &str = "15.0.7";
+
+// 级别对应的 Code Quality severity
+fn code_quality_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "blocker",
+        Severity::High => "critical",
+        Severity::Medium => "major",
+        Severity::Low => "minor",
+        Severity::Info | Severity::Unknown => "info",
+    }
+}
+
+// 级别对应的 SAST severity
+fn sast_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "Critical",
+        Severity::High => "High",
+        Severity::Medium => "Medium",
+        Severity::Low => "Low",
+        Severity::Info => "Info",
+        Severity::Unknown => "Unknown",
+    }
+}
+
+fn description(problem: &Problem) -> String {
+    match &problem.solution {
+        Some(solution) if !solution.wiki_description.is_empty() => {
+            solution.wiki_description.clone()
+        }
+        _ => problem.error_code.clone(),
+    }
+}
+
+// 统一路径分隔符, GitLab 按仓库内的相对路径匹配文件
+fn path(problem: &Problem) -> String {
+    problem
+        .file_path
+        .as_deref()
+        .unwrap_or_default()
+        .replace('\\', "/")
+}
+
+// 缺陷指纹转换为 UUID 格式的 id, 指纹不变则 id 不变
+fn uuid(fingerprint: &str) -> String {
+    let hex: String = fingerprint
+        .chars()
+        .chain("0".repeat(32).chars())
+        .take(32)
+        .collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+// 被忽略的缺陷不输出到 GitLab 报告中
+fn reported(result: &TaskResult) -> impl Iterator<Item = &Problem> {
+    result.problems.iter().filter(|p| p.suppression.is_none())
+}
+
+// 将检测结果转换为 GitLab Code Quality(CodeClimate 格式)报告
+pub(crate) fn code_quality(result: &TaskResult) -> Value {
+    let issues: Vec<Value> = reported(result)
+        .map(|problem| {
+            json!({
+                "type": "issue",
+                "check_name": problem.error_code,
+                "description": description(problem),
+                "categories": ["Security"],
+                "severity": code_quality_severity(result.severity(problem.severity_level)),
+                "fingerprint": problem.fingerprint,
+                "location": {
+                    "path": path(problem),
+                    "lines": { "begin": problem.line.unwrap_or(1).max(1) }
+                }
+            })
+        })
+        .collect();
+    Value::Array(issues)
+}
+
+// 将检测结果转换为 GitLab SAST 报告(gl-sast-report.json)
+pub(crate) fn sast(result: &TaskResult) -> Value {
+    let vulnerabilities: Vec<Value> = reported(result)
+        .map(|problem| {
+            let mut identifiers = vec![json!({
+                "type": "codepecker_error_code",
+                "name": problem.error_code,
+                "value": problem.error_code
+            })];
+            if let Some(cwe) = problem.cwe() {
+                identifiers.push(json!({
+                    "type": "cwe",
+                    "name": format!("CWE-{cwe}"),
+                    "value": cwe,
+                    "url": format!("https://cwe.mitre.org/data/definitions/{cwe}.html")
+                }));
+            }
+            let line = problem.line.unwrap_or(1).max(1);
+            let mut vulnerability = json!({
+                "id": uuid(problem.fingerprint.as_deref().unwrap_or_default()),
+                "name": problem.error_code,
+                "description": description(problem),
+                "severity": sast_severity(result.severity(problem.severity_level)),
+                "identifiers": identifiers,
+                "location": {
+                    "file": path(problem),
+                    "start_line": line,
+                    "end_line": line
+                }
+            });
+            if let Some(solution) = problem
+                .solution
+                .as_ref()
+                .filter(|s| !s.wiki_detail.is_empty())
+            {
+                vulnerability["solution"] = json!(solution.wiki_detail);
+            }
+            vulnerability
+        })
+        .collect();
+    let time = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    let tool = json!({
+        "id": "codepecker",
+        "name": "CodePecker",
+        "version": env!("CARGO_PKG_VERSION"),
+        "vendor": { "name": "CodePecker" }
+    });
+    json!({
+        "version": SAST_SCHEMA_VERSION,
+        "scan": {
+            "analyzer": tool,
+            "scanner": tool,
+            "type": "sast",
+            "start_time": time,
+            "end_time": time,
+            "status": "success"
+        },
+        "vulnerabilities": vulnerabilities
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{model::test_problem, report::test_result};
+
+    // (行号, 所在行的源码)
+    fn result(lines: &[(u64, &str)]) -> TaskResult {
+        test_result(
+            lines
+                .iter()
+                .map(|(line, code)| {
+                    let mut problem = test_problem(json!({
+                        "errorCode": "HARDCODED_PASSWORD",
+                        "line": line,
+                        "severityLevel": 2
+                    }));
+                    problem.fingerprint = Some(problem.compute_fingerprint(Some(code)));
+                    problem
+                })
+                .collect(),
+        )
+    }
+
+    fn ids(report: &Value, key: &str) -> Vec<String> {
+        report
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item[key].as_str().unwrap().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn same_rule_in_same_file_has_distinct_ids() {
+        let result = result(&[(10, "String a = \"pw1\";"), (20, "String b = \"pw2\";")]);
+        let fingerprints = ids(&code_quality(&result), "fingerprint");
+        assert_ne!(fingerprints[0], fingerprints[1]);
+        assert_eq!(
+            fingerprints[0],
+            result.problems[0].fingerprint.clone().unwrap()
+        );
+        let sast_ids = ids(&sast(&result)["vulnerabilities"], "id");
+        assert_ne!(sast_ids[0], sast_ids[1]);
+    }
+
+    #[test]
+    fn ids_stable_when_findings_added_above() {
+        let before = result(&[(10, "String a = \"pw1\";"), (20, "String b = \"pw2\";")]);
+        let after = result(&[
+            (5, "String c = \"pw3\";"),
+            (11, "String a = \"pw1\";"),
+            (21, "String b = \"pw2\";"),
+        ]);
+        let before = ids(&sast(&before)["vulnerabilities"], "id");
+        let after = ids(&sast(&after)["vulnerabilities"], "id");
+        assert_eq!(before, after[1..]);
+    }
+}
@@ -0,0 +1,310 @@
+use std::{cmp::Reverse, collections::BTreeMap, fmt::Write};
+
+use serde_json::Value;
+
+use super::{escape, TaskResult};
+use crate::{
+    model::{BaselineState, Problem, Snippet, Solution},
+    severity::Severity,
+};
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", "Microsoft YaHei", sans-serif; margin: 2em auto; max-width: 1200px; color: #24292f; }
+h1 { font-size: 1.6em; }
+table.stats { border-collapse: collapse; margin-bottom: 1.5em; }
+table.stats td, table.stats th { border: 1px solid #d0d7de; padding: 4px 12px; text-align: left; }
+details { margin: 0.5em 0; }
+details.severity > summary { font-size: 1.3em; font-weight: bold; padding: 6px; border-radius: 4px; }
+details.rule { margin-left: 1em; border-left: 3px solid #d0d7de; padding-left: 1em; }
+details.rule > summary { font-size: 1.1em; font-weight: bold; }
+summary { cursor: pointer; }
+.sev-critical > summary { background: #ffebe9; }
+.sev-high > summary { background: #fff1e5; }
+.sev-medium > summary { background: #fff8c5; }
+.sev-low > summary { background: #ddf4ff; }
+.sev-info > summary, .sev-unknown > summary { background: #f6f8fa; }
+.wiki { background: #f6f8fa; padding: 8px 12px; border-radius: 4px; }
+.file { font-family: monospace; font-weight: bold; margin-top: 1em; }
+.finding { margin: 0.5em 0 1em 1em; }
+.badge { display: inline-block; font-size: 0.8em; padding: 0 6px; border-radius: 8px; background: #eaeef2; margin-left: 6px; }
+pre { background: #f6f8fa; padding: 8px; overflow-x: auto; margin: 4px 0; }
+table.code { border-collapse: collapse; font-family: monospace; font-size: 0.9em; width: 100%; background: #f6f8fa; }
+table.code td { padding: 0 8px; white-space: pre; }
+table.code td.num { color: #8c959f; text-align: right; user-select: none; width: 1%; }
+table.code tr.hit { background: #ffebe9; font-weight: bold; }
+ol.trace li { margin-bottom: 0.5em; }
+"#;
+
+// 统计项的值, 字符串不带引号输出
+fn stat_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+// 带行号的源码片段, 高亮缺陷所在行
+fn snippet(html: &mut String, snippet: &Snippet, line: Option<u64>) {
+    html.push_str("<table class=\"code\">");
+    for (number, code) in (snippet.start_line..).zip(snippet.code.lines()) {
+        let class = if Some(number) == line {
+            " class=\"hit\""
+        } else {
+            ""
+        };
+        let _ = write!(
+            html,
+            "<tr{class}><td class=\"num\">{number}</td><td>{}</td></tr>",
+            escape(code)
+        );
+    }
+    html.push_str("</table>");
+}
+
+// 规则的wiki说明、详情及示例
+fn wiki(html: &mut String, solution: Option<&Solution>) {
+    let Some(solution) = solution else {
+        return;
+    };
+    html.push_str("<div class=\"wiki\">");
+    let _ = write!(html, "<p>{}</p>", escape(&solution.wiki_description));
+    if !solution.wiki_detail.is_empty() {
+        let _ = write!(html, "<p>{}</p>", escape(&solution.wiki_detail));
+    }
+    if !solution.wiki_example.is_empty() {
+        let _ = write!(html, "<pre>{}</pre>", escape(&solution.wiki_example));
+    }
+    html.push_str("</div>");
+}
+
+fn finding(html: &mut String, problem: &Problem) {
+    html.push_str("<div class=\"finding\">");
+    let line = problem
+        .line
+        .map_or_else(|| "-".to_owned(), |line| line.to_string());
+    let _ = write!(html, "<div>第 {line} 行");
+    match problem.baseline_state {
+        Some(BaselineState::New) => html.push_str("<span class=\"badge\">新增</span>"),
+        Some(BaselineState::Unchanged) => {
+            html.push_str("<span class=\"badge\">基线中已存在</span>")
+        }
+        _ => {}
+    }
+    if let Some(suppression) = &problem.suppression {
+        let _ = write!(
+            html,
+            "<span class=\"badge\">已忽略: {}</span>",
+            escape(&suppression.reason)
+        );
+    }
+    html.push_str("</div>");
+    if let Some(code) = &problem.snippet {
+        snippet(html, code, problem.line);
+    }
+    if !problem.trace_block.is_empty() {
+        html.push_str("<details><summary>跟踪步骤</summary><ol class=\"trace\">");
+        for trace_block in &problem.trace_block {
+            let _ = write!(
+                html,
+                "<li><code>{}:{}</code> {}",
+                escape(trace_block.file.as_deref().unwrap_or("-")),
+                trace_block
+                    .line
+                    .map_or_else(|| "-".to_owned(), |line| line.to_string()),
+                escape(trace_block.message.as_deref().unwrap_or_default())
+            );
+            if let Some(code) = &trace_block.snippet {
+                snippet(html, code, trace_block.line);
+            }
+            html.push_str("</li>");
+        }
+        html.push_str("</ol></details>");
+    }
+    html.push_str("</div>");
+}
+
+// 开源组件检测结果
+fn components(html: &mut String, result: &TaskResult) {
+    let Some(components) = &result.components else {
+        return;
+    };
+    let _ = writeln!(
+        html,
+        "<details class=\"severity\" open><summary>开源组件 ({})</summary>",
+        components.components.len()
+    );
+    html.push_str(
+        "<table class=\"stats\"><tr><th>组件</th><th>版本</th><th>级别</th>\
+         <th>漏洞</th><th>许可证</th><th>文件</th></tr>",
+    );
+    for component in &components.components {
+        let _ = write!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&component.name),
+            escape(component.version.as_deref().unwrap_or("-")),
+            result.severity(component.severity_level),
+            escape(&component.cves.join(", ")),
+            escape(component.license.as_deref().unwrap_or("-")),
+            escape(component.file_path.as_deref().unwrap_or("-"))
+        );
+    }
+    html.push_str("</table>\n</details>\n");
+}
+
+// 将检测结果生成为不依赖外部资源的 HTML 报告, 缺陷按级别、规则、文件分组
+pub(crate) fn build(result: &TaskResult) -> String {
+    // 级别(从高到低) -> errorCode -> 文件 -> 缺陷
+    type Groups<'a> =
+        BTreeMap<Reverse<Severity>, BTreeMap<&'a str, BTreeMap<&'a str, Vec<&'a Problem>>>>;
+    let mut groups: Groups<'_> = BTreeMap::new();
+    for problem in &result.problems {
+        groups
+            .entry(Reverse(result.severity(problem.severity_level)))
+            .or_default()
+            .entry(&problem.error_code)
+            .or_default()
+            .entry(problem.file_path.as_deref().unwrap_or("-"))
+            .or_default()
+            .push(problem);
+    }
+
+    let mut html = String::new();
+    let title = format!("CodePecker 检测报告 - {}", result.task_id);
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape(&title),
+        escape(&title)
+    );
+    let _ = writeln!(
+        html,
+        "<p>生成时间: {}, 筛选级别: {}, 缺陷数量: {}</p>",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        escape(&result.severity.to_string()),
+        result.problems.len()
+    );
+
+    html.push_str("<h2>统计</h2>\n<table class=\"stats\">");
+    for (key, value) in &result.info.counts {
+        let _ = write!(
+            html,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            escape(key),
+            escape(&stat_value(value))
+        );
+    }
+    for (Reverse(severity), rules) in &groups {
+        let count: usize = rules
+            .values()
+            .flat_map(|files| files.values())
+            .map(Vec::len)
+            .sum();
+        let _ = write!(html, "<tr><th>{severity}</th><td>{count}</td></tr>");
+    }
+    if let Some(fixed) = &result.fixed {
+        let _ = write!(html, "<tr><th>已修复</th><td>{}</td></tr>", fixed.len());
+    }
+    html.push_str("</table>\n");
+
+    for (Reverse(severity), rules) in &groups {
+        let count: usize = rules
+            .values()
+            .flat_map(|files| files.values())
+            .map(Vec::len)
+            .sum();
+        let _ = writeln!(
+            html,
+            "<details class=\"severity sev-{severity}\" open><summary>{severity} ({count})</summary>"
+        );
+        for (error_code, files) in rules {
+            let count: usize = files.values().map(Vec::len).sum();
+            let _ = writeln!(
+                html,
+                "<details class=\"rule\" open><summary>{} ({count})</summary>",
+                escape(error_code)
+            );
+            let solution = files
+                .values()
+                .flatten()
+                .find_map(|problem| problem.solution.as_ref());
+            wiki(&mut html, solution);
+            for (file, problems) in files {
+                let _ = writeln!(html, "<div class=\"file\">{}</div>", escape(file));
+                for problem in problems {
+                    finding(&mut html, problem);
+                }
+            }
+            html.push_str("</details>\n");
+        }
+        html.push_str("</details>\n");
+    }
+    components(&mut html, result);
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        model::{test_problem, Suppression},
+        report::test_result,
+    };
+
+    #[test]
+    fn groups_by_severity_rule_and_file() {
+        let result = test_result(vec![
+            test_problem(json!({ "errorCode": "LOG", "severityLevel": 3 })),
+            test_problem(json!({ "severityLevel": 1, "filePath": "src/B.java" })),
+            test_problem(json!({ "severityLevel": 1 })),
+        ]);
+        let html = build(&result);
+        let critical = html.find("<summary>critical (2)</summary>").unwrap();
+        let medium = html.find("<summary>medium (1)</summary>").unwrap();
+        assert!(critical < medium);
+        assert!(html.contains("<summary>NPE (2)</summary>"));
+        let a = html.find("<div class=\"file\">src/A.java</div>").unwrap();
+        let b = html.find("<div class=\"file\">src/B.java</div>").unwrap();
+        assert!(critical < a && a < b && b < medium);
+        assert!(html.contains("<tr><th>critical</th><td>2</td></tr>"));
+    }
+
+    #[test]
+    fn escapes_server_content() {
+        let result = test_result(vec![test_problem(json!({
+            "errorCode": "<script>alert(1)</script>",
+            "filePath": "src/<A>.java",
+            "solution": { "wiki_description": "a & b", "wiki_example": "x < y" },
+            "traceBlock": [{ "file": "src/A.java", "line": 3, "message": "\"quoted\"" }]
+        }))]);
+        let html = build(&result);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(html.contains("src/&lt;A&gt;.java"));
+        assert!(html.contains("<p>a &amp; b</p>"));
+        assert!(html.contains("<pre>x &lt; y</pre>"));
+        assert!(html.contains("&quot;quoted&quot;"));
+    }
+
+    #[test]
+    fn finding_highlights_line_and_shows_state() {
+        let mut problem = test_problem(json!({
+            "snippet": { "startLine": 3, "endLine": 5, "code": "a\nb < c\nd" }
+        }));
+        problem.baseline_state = Some(BaselineState::New);
+        problem.suppression = Some(Suppression {
+            reason: "误报".to_owned(),
+            expires: None,
+        });
+        let mut html = String::new();
+        finding(&mut html, &problem);
+        assert!(html.contains("<tr class=\"hit\"><td class=\"num\">4</td><td>b &lt; c</td></tr>"));
+        assert!(html.contains("<tr><td class=\"num\">3</td><td>a</td></tr>"));
+        assert!(html.contains("<span class=\"badge\">新增</span>"));
+        assert!(html.contains("<span class=\"badge\">已忽略: 误报</span>"));
+    }
+}
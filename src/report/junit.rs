@@ -0,0 +1,198 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use super::{escape, TaskResult};
+use crate::{
+    model::{BaselineState, Problem},
+    severity::Severity,
+};
+
+// 缺陷的位置, eg. src/A.java:4
+fn location(problem: &Problem) -> String {
+    match problem.line {
+        Some(line) => format!("{}:{line}", problem.file_path.as_deref().unwrap_or("-")),
+        None => problem.file_path.as_deref().unwrap_or("-").to_owned(),
+    }
+}
+
+// 失败详情: 源码片段及跟踪步骤
+fn detail(problem: &Problem) -> String {
+    let mut detail = String::new();
+    if let Some(snippet) = &problem.snippet {
+        for (number, code) in (snippet.start_line..).zip(snippet.code.lines()) {
+            let marker = if Some(number) == problem.line {
+                ">"
+            } else {
+                " "
+            };
+            let _ = writeln!(detail, "{marker}{number:>6} | {code}");
+        }
+    }
+    for (index, trace_block) in problem.trace_block.iter().enumerate() {
+        let _ = writeln!(
+            detail,
+            "{}. {}:{} {}",
+            index + 1,
+            trace_block.file.as_deref().unwrap_or("-"),
+            trace_block
+                .line
+                .map_or_else(|| "-".to_owned(), |line| line.to_string()),
+            trace_block.message.as_deref().unwrap_or_default()
+        );
+    }
+    detail
+}
+
+// 缺陷是否视为失败的测试用例: 被忽略或基线中已存在的缺陷不算失败,
+// 设置质量门禁时只有数量超出阈值的级别的缺陷算失败
+fn is_failure(problem: &Problem, severity: Severity, exceeded: Option<&[Severity]>) -> bool {
+    if problem.suppression.is_some() || problem.baseline_state == Some(BaselineState::Unchanged) {
+        return false;
+    }
+    match exceeded {
+        Some(severities) => severities.contains(&severity),
+        None => true,
+    }
+}
+
+// 将检测结果转换为 JUnit XML, 每个errorCode为一个testsuite, 每个缺陷为一个testcase
+pub(crate) fn build(result: &TaskResult) -> String {
+    let exceeded = result.gate.as_ref().map(|gate| {
+        gate.exceeded(
+            result
+                .gated_problems()
+                .map(|p| result.severity(p.severity_level)),
+        )
+    });
+    let is_failure = |problem: &Problem| {
+        is_failure(
+            problem,
+            result.severity(problem.severity_level),
+            exceeded.as_deref(),
+        )
+    };
+    let mut suites: BTreeMap<&str, Vec<&Problem>> = BTreeMap::new();
+    for problem in &result.problems {
+        suites.entry(&problem.error_code).or_default().push(problem);
+    }
+    let total_failures = result.problems.iter().filter(|p| is_failure(p)).count();
+    // 没有缺陷时输出一个通过的用例, 使CI显示检测通过
+    let tests = result.problems.len().max(1);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"CodePecker {}\" tests=\"{tests}\" failures=\"{total_failures}\">",
+        escape(&result.task_id)
+    );
+    if suites.is_empty() {
+        xml.push_str("  <testsuite name=\"codepecker\" tests=\"1\" failures=\"0\">\n");
+        let _ = writeln!(
+            xml,
+            "    <testcase name=\"{}的缺陷\" classname=\"codepecker\"/>",
+            escape(&result.severity.description())
+        );
+        xml.push_str("  </testsuite>\n");
+    }
+    for (error_code, problems) in &suites {
+        let failures = problems.iter().filter(|p| is_failure(p)).count();
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\">",
+            escape(error_code),
+            problems.len()
+        );
+        for problem in problems {
+            let location = location(problem);
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\"",
+                escape(&location),
+                escape(error_code)
+            );
+            if !is_failure(problem) {
+                xml.push_str("/>\n");
+                continue;
+            }
+            let description = problem
+                .solution
+                .as_ref()
+                .map(|s| s.wiki_description.as_str())
+                .filter(|d| !d.is_empty())
+                .unwrap_or(error_code);
+            let _ = writeln!(
+                xml,
+                ">\n      <failure message=\"{}: {}\" type=\"{}\">{}</failure>\n    </testcase>",
+                escape(&location),
+                escape(description),
+                result.severity(problem.severity_level),
+                escape(&detail(problem))
+            );
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        model::{test_problem, Suppression},
+        report::test_result,
+    };
+
+    #[test]
+    fn suite_per_error_code() {
+        let mut suppressed = test_problem(json!({ "line": 9 }));
+        suppressed.suppression = Some(Suppression {
+            reason: "误报".to_owned(),
+            expires: None,
+        });
+        let mut unchanged = test_problem(json!({ "errorCode": "SQLI", "filePath": "src/B.java" }));
+        unchanged.baseline_state = Some(BaselineState::Unchanged);
+        let result = test_result(vec![
+            test_problem(json!({
+                "severityLevel": 2,
+                "snippet": { "startLine": 3, "endLine": 4, "code": "s = null;\ns.length();" },
+                "traceBlock": [{ "file": "src/A.java", "line": 3, "message": "s = null" }]
+            })),
+            suppressed,
+            unchanged,
+        ]);
+        let xml = build(&result);
+        assert!(xml.contains("<testsuites name=\"CodePecker 1\" tests=\"3\" failures=\"1\">"));
+        assert!(xml.contains("<testsuite name=\"NPE\" tests=\"2\" failures=\"1\">"));
+        assert!(xml.contains("<testsuite name=\"SQLI\" tests=\"1\" failures=\"0\">"));
+        assert!(xml.contains(
+            "<failure message=\"src/A.java:4: NPE\" type=\"high\">      3 | s = null;\n&gt;     4 | s.length();\n1. src/A.java:3 s = null\n</failure>"
+        ));
+        assert!(xml.contains("<testcase name=\"src/A.java:9\" classname=\"NPE\"/>"));
+        assert!(xml.contains("<testcase name=\"src/B.java:4\" classname=\"SQLI\"/>"));
+    }
+
+    #[test]
+    fn gate_limits_failures_to_exceeded_severities() {
+        let mut result = test_result(vec![
+            test_problem(json!({ "severityLevel": 1 })),
+            test_problem(json!({ "errorCode": "LOG", "severityLevel": 2 })),
+        ]);
+        result.gate = Some("critical=0,high=1".parse().unwrap());
+        let xml = build(&result);
+        assert!(
+            xml.contains("failures=\"1\">\n  <testsuite name=\"LOG\" tests=\"1\" failures=\"0\">")
+        );
+        assert!(xml.contains("<testsuite name=\"NPE\" tests=\"1\" failures=\"1\">"));
+    }
+
+    #[test]
+    fn empty_result_passes_with_filter_description() {
+        let mut result = test_result(Vec::new());
+        result.severity = "=medium".parse().unwrap();
+        let xml = build(&result);
+        assert!(xml.contains("tests=\"1\" failures=\"0\""));
+        assert!(xml.contains("<testcase name=\"medium 级别的缺陷\" classname=\"codepecker\"/>"));
+    }
+}
@@ -0,0 +1,346 @@
+use std::{cmp::Reverse, collections::BTreeMap, fmt::Write};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde_json::Value;
+
+use super::TaskResult;
+use crate::{
+    model::{BaselineState, Problem},
+    severity::Severity,
+};
+
+/// Markdown 摘要的参数
+#[derive(Debug, Clone)]
+pub(crate) struct SummaryOptions<'a> {
+    /// 列出的缺陷数量
+    pub(crate) top: usize,
+    /// 最大字符数, 超出时折叠各部分并截断缺陷列表
+    pub(crate) limit: usize,
+    /// 仓库文件链接的基础地址, eg. https://github.com/owner/repo/blob, 与 commit_sha 都设置时才生成链接
+    pub(crate) repo_url: Option<&'a str>,
+    pub(crate) commit_sha: Option<&'a str>,
+}
+
+// 表格单元格中不能有换行及竖线, 服务端返回的内容不按 HTML 渲染
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace(['\r', '\n'], " ")
+}
+
+// 链接路径中需要编码的字符, 包括 Markdown 链接中的括号及方括号
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}')
+    .add(b'(')
+    .add(b')')
+    .add(b'[')
+    .add(b']')
+    .add(b'|')
+    .add(b'\\')
+    .add(b'^');
+
+// 缺陷位置, 设置仓库地址及提交时为文件链接, eg. [src/A.java:4](https://.../blob/<sha>/src/A.java#L4).
+// 没有提交的链接在 GitHub 及 GitLab 上无法打开, 只输出文本
+fn location(problem: &Problem, options: &SummaryOptions<'_>) -> String {
+    let path = problem
+        .file_path
+        .as_deref()
+        .unwrap_or("-")
+        .replace('\\', "/");
+    let text = match problem.line {
+        Some(line) => format!("{path}:{line}"),
+        None => path.clone(),
+    };
+    let (Some(base), Some(sha), Some(_)) =
+        (options.repo_url, options.commit_sha, &problem.file_path)
+    else {
+        return format!("`{}`", cell(&text));
+    };
+    let mut url = format!(
+        "{}/{}",
+        base.trim_end_matches('/'),
+        utf8_percent_encode(sha, PATH_SEGMENT)
+    );
+    for segment in path.trim_start_matches('/').split('/') {
+        let _ = write!(url, "/{}", utf8_percent_encode(segment, PATH_SEGMENT));
+    }
+    if let Some(line) = problem.line {
+        let _ = write!(url, "#L{line}");
+    }
+    format!("[{}]({url})", cell(&text))
+}
+
+// 摘要的一部分, 超出长度限制时折叠为 <details>
+struct Section {
+    title: String,
+    body: String,
+}
+
+impl Section {
+    fn render(&self, collapsed: bool) -> String {
+        if collapsed {
+            format!(
+                "<details>\n<summary>{}</summary>\n\n{}\n</details>\n\n",
+                self.title, self.body
+            )
+        } else {
+            format!("### {}\n\n{}\n", self.title, self.body)
+        }
+    }
+}
+
+// 各级别缺陷数量
+fn severity_table(result: &TaskResult) -> String {
+    let mut counts: BTreeMap<Severity, usize> = BTreeMap::new();
+    for problem in result.gated_problems() {
+        *counts
+            .entry(result.severity(problem.severity_level))
+            .or_default() += 1;
+    }
+    let mut table = String::from("| 级别 | 数量 |\n| --- | ---: |\n");
+    // 未知级别只在存在时列出
+    let unknown = counts
+        .contains_key(&Severity::Unknown)
+        .then_some(Severity::Unknown);
+    for severity in Severity::KNOWN.into_iter().chain(unknown) {
+        let _ = writeln!(
+            table,
+            "| {severity} | {} |",
+            counts.get(&severity).copied().unwrap_or_default()
+        );
+    }
+    let _ = writeln!(
+        table,
+        "| **合计** | **{}** |",
+        counts.values().sum::<usize>()
+    );
+    table
+}
+
+// queryStatistics 接口返回的统计项
+fn statistics(result: &TaskResult) -> Option<Section> {
+    if result.info.counts.is_empty() {
+        return None;
+    }
+    let mut body = String::from("| 统计项 | 值 |\n| --- | ---: |\n");
+    for (key, value) in &result.info.counts {
+        let value = match value {
+            Value::String(s) => s.clone(),
+            _ => value.to_string(),
+        };
+        let _ = writeln!(body, "| {} | {} |", cell(key), cell(&value));
+    }
+    Some(Section {
+        title: "服务端统计".to_owned(),
+        body,
+    })
+}
+
+// 按级别排序后的前 top 个缺陷
+fn findings(
+    result: &TaskResult,
+    problems: &[&Problem],
+    top: usize,
+    options: &SummaryOptions<'_>,
+) -> Section {
+    let mut body = String::from("| 级别 | 规则 | 位置 | 说明 |\n| --- | --- | --- | --- |\n");
+    for problem in problems.iter().take(top) {
+        let description = problem
+            .solution
+            .as_ref()
+            .map(|s| s.wiki_description.as_str())
+            .unwrap_or_default();
+        let _ = writeln!(
+            body,
+            "| {} | `{}` | {} | {} |",
+            result.severity(problem.severity_level),
+            cell(&problem.error_code),
+            location(problem, options),
+            cell(description)
+        );
+    }
+    let rest = problems.len().saturating_sub(top);
+    if rest > 0 {
+        let _ = writeln!(body, "\n其余 {rest} 个缺陷未列出.");
+    }
+    Section {
+        title: format!("主要缺陷 (前 {} 个)", top.min(problems.len())),
+        body,
+    }
+}
+
+// 将检测结果生成为 Markdown 摘要, 可用于 $GITHUB_STEP_SUMMARY 或合并请求评论
+pub(crate) fn build(result: &TaskResult, options: &SummaryOptions<'_>) -> String {
+    let mut header = format!("## CodePecker 检测结果 ({})\n\n", cell(&result.task_id));
+    header.push_str(&severity_table(result));
+    let suppressed = result
+        .problems
+        .iter()
+        .filter(|p| p.suppression.is_some())
+        .count();
+    let mut notes = Vec::new();
+    if let Some(fixed) = &result.fixed {
+        let unchanged = result
+            .problems
+            .iter()
+            .filter(|p| p.baseline_state == Some(BaselineState::Unchanged))
+            .count();
+        notes.push(format!("基线中已存在 {unchanged} 个"));
+        notes.push(format!("已修复 {} 个", fixed.len()));
+    }
+    if suppressed > 0 {
+        notes.push(format!("已忽略 {suppressed} 个"));
+    }
+    if !notes.is_empty() {
+        let _ = writeln!(header, "\n未计入合计: {}.", notes.join(", "));
+    }
+    header.push('\n');
+
+    let mut problems: Vec<&Problem> = result.gated_problems().collect();
+    problems.sort_by_key(|p| {
+        (
+            Reverse(result.severity(p.severity_level)),
+            p.file_path.clone(),
+            p.line,
+        )
+    });
+    let statistics = statistics(result);
+    let render = |top: usize, collapsed: bool| {
+        let mut markdown = header.clone();
+        if top > 0 && !problems.is_empty() {
+            markdown.push_str(&findings(result, &problems, top, options).render(collapsed));
+        }
+        if let Some(statistics) = &statistics {
+            markdown.push_str(&statistics.render(collapsed));
+        }
+        markdown
+    };
+
+    let mut top = options.top;
+    let markdown = render(top, false);
+    if markdown.chars().count() <= options.limit {
+        return markdown;
+    }
+    // 超出长度限制时折叠各部分, 仍然超出时逐步减少列出的缺陷
+    loop {
+        let markdown = render(top, true);
+        if markdown.chars().count() <= options.limit || top == 0 {
+            return markdown;
+        }
+        top /= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        model::{test_problem, Suppression},
+        report::test_result,
+    };
+
+    fn options<'a>(repo_url: Option<&'a str>, commit_sha: Option<&'a str>) -> SummaryOptions<'a> {
+        SummaryOptions {
+            top: 10,
+            limit: 65000,
+            repo_url,
+            commit_sha,
+        }
+    }
+
+    fn problem(path: &str) -> Problem {
+        test_problem(json!({ "filePath": path }))
+    }
+
+    #[test]
+    fn location_without_commit_is_plain_text() {
+        let problem = problem("src/A.java");
+        let repo = Some("https://github.com/o/r/blob/");
+        assert_eq!(location(&problem, &options(repo, None)), "`src/A.java:4`");
+        assert_eq!(
+            location(&problem, &options(None, Some("abc"))),
+            "`src/A.java:4`"
+        );
+    }
+
+    #[test]
+    fn location_link_encodes_path() {
+        let problem = problem("src\\中文 (1)\\A#B.java");
+        assert_eq!(
+            location(
+                &problem,
+                &options(Some("https://github.com/o/r/blob/"), Some("abc"))
+            ),
+            "[src/中文 (1)/A#B.java:4](https://github.com/o/r/blob/abc/src/%E4%B8%AD%E6%96%87%20%281%29/A%23B.java#L4)"
+        );
+    }
+
+    #[test]
+    fn summary_counts_gated_problems() {
+        let mut suppressed = test_problem(json!({ "severityLevel": 1 }));
+        suppressed.suppression = Some(Suppression {
+            reason: "误报".to_owned(),
+            expires: None,
+        });
+        let mut unchanged = test_problem(json!({ "severityLevel": 1, "line": 8 }));
+        unchanged.baseline_state = Some(BaselineState::Unchanged);
+        let mut result = test_result(vec![
+            test_problem(
+                json!({ "errorCode": "LOG", "severityLevel": 3, "filePath": "src/B.java" }),
+            ),
+            test_problem(json!({
+                "severityLevel": 1,
+                "solution": { "wiki_description": "a|b\nc" }
+            })),
+            suppressed,
+            unchanged,
+        ]);
+        result.fixed = Some(Vec::new());
+        let markdown = build(&result, &options(None, None));
+        assert!(markdown.starts_with("## CodePecker 检测结果 (1)\n"));
+        assert!(markdown.contains("| critical | 1 |\n| high | 0 |\n| medium | 1 |"));
+        assert!(markdown.contains("| **合计** | **2** |"));
+        assert!(!markdown.contains("| unknown |"));
+        assert!(markdown.contains("未计入合计: 基线中已存在 1 个, 已修复 0 个, 已忽略 1 个."));
+        // 按级别从高到低列出, 表格单元格中的竖线及换行被转义
+        let critical = markdown
+            .find("| critical | `NPE` | `src/A.java:4` | a\\|b c |")
+            .unwrap();
+        let medium = markdown
+            .find("| medium | `LOG` | `src/B.java:4` |  |")
+            .unwrap();
+        assert!(critical < medium);
+    }
+
+    #[test]
+    fn summary_truncated_to_limit() {
+        let problems = (1..=40)
+            .map(|line| test_problem(json!({ "line": line, "severityLevel": 2 })))
+            .collect();
+        let result = test_result(problems);
+        let mut options = options(None, None);
+        options.top = 5;
+        let markdown = build(&result, &options);
+        assert!(markdown.contains("### 主要缺陷 (前 5 个)"));
+        assert!(markdown.contains("其余 35 个缺陷未列出."));
+
+        options.top = 40;
+        options.limit = 1000;
+        let markdown = build(&result, &options);
+        assert!(markdown.chars().count() <= 1000, "{markdown}");
+        assert!(markdown.contains("<details>\n<summary>主要缺陷 (前 "));
+        assert!(markdown.contains("| high | 40 |"));
+    }
+}
@@ -0,0 +1,278 @@
+use serde_json::{json, Value};
+
+use super::TaskResult;
+use crate::{
+    model::{BaselineState, Problem, Snippet, Solution},
+    severity::Severity,
+};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+// 级别对应的 SARIF level
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Info | Severity::Unknown => "note",
+    }
+}
+
+// 供 GitHub code scanning 排序使用的 security-severity 分值
+fn security_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "9.5",
+        Severity::High => "8.0",
+        Severity::Medium => "5.5",
+        Severity::Low => "3.0",
+        Severity::Info | Severity::Unknown => "1.0",
+    }
+}
+
+// SARIF 要求行号从1开始
+// 有源码片段时, 附上所在行及上下文的源码
+fn physical_location(file: &str, line: Option<u64>, snippet: Option<&Snippet>) -> Value {
+    let start_line = line.unwrap_or(1).max(1);
+    let mut location = json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": file.replace('\\', "/") },
+            "region": { "startLine": start_line }
+        }
+    });
+    if let Some(snippet) = snippet {
+        if let Some(code) = snippet.line(start_line) {
+            location["physicalLocation"]["region"]["snippet"] = json!({ "text": code });
+        }
+        location["physicalLocation"]["contextRegion"] = json!({
+            "startLine": snippet.start_line,
+            "endLine": snippet.end_line,
+            "snippet": { "text": snippet.code }
+        });
+    }
+    location
+}
+
+fn rule(problem: &Problem, severity: Severity) -> Value {
+    let solution = problem.solution.clone().unwrap_or_default();
+    let Solution {
+        wiki_description: description,
+        wiki_detail: detail,
+        wiki_example: example,
+    } = solution;
+    let short = if description.is_empty() {
+        problem.error_code.clone()
+    } else {
+        description
+    };
+    let mut markdown = format!("**{short}**");
+    if !detail.is_empty() {
+        markdown.push_str(&format!("\n\n{detail}"));
+    }
+    if !example.is_empty() {
+        markdown.push_str(&format!("\n\n```\n{example}\n```"));
+    }
+    let help = [short.as_str(), detail.as_str(), example.as_str()]
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    json!({
+        "id": problem.error_code,
+        "name": problem.error_code,
+        "shortDescription": { "text": short },
+        "fullDescription": { "text": if detail.is_empty() { &short } else { &detail } },
+        "help": {
+            "text": help,
+            "markdown": markdown
+        },
+        "defaultConfiguration": { "level": level(severity) },
+        "properties": { "security-severity": security_severity(severity) }
+    })
+}
+
+fn code_flows(problem: &Problem) -> Option<Value> {
+    let locations: Vec<Value> = problem
+        .trace_block
+        .iter()
+        .filter_map(|trace_block| {
+            let file = trace_block.file.as_deref()?;
+            let mut location =
+                physical_location(file, trace_block.line, trace_block.snippet.as_ref());
+            if let Some(message) = &trace_block.message {
+                location["message"] = json!({ "text": message });
+            }
+            Some(json!({ "location": location }))
+        })
+        .collect();
+    if locations.is_empty() {
+        return None;
+    }
+    Some(json!([{ "threadFlows": [{ "locations": locations }] }]))
+}
+
+// 将筛选后的缺陷转换为 SARIF 2.1.0 文档
+pub(crate) fn build(result: &TaskResult) -> Value {
+    let problems = &result.problems;
+    let mut rules: Vec<Value> = Vec::new();
+    let mut results = Vec::with_capacity(problems.len());
+    for problem in problems {
+        let severity = result.severity(problem.severity_level);
+        let rule_index = match rules.iter().position(|r| r["id"] == problem.error_code) {
+            Some(index) => index,
+            None => {
+                rules.push(rule(problem, severity));
+                rules.len() - 1
+            }
+        };
+        let message = match &problem.solution {
+            Some(solution) if !solution.wiki_description.is_empty() => {
+                solution.wiki_description.clone()
+            }
+            _ => problem.error_code.clone(),
+        };
+        let mut result = json!({
+            "ruleId": problem.error_code,
+            "partialFingerprints": { "codepecker/v1": problem.fingerprint },
+            "ruleIndex": rule_index,
+            "level": level(severity),
+            "message": { "text": message },
+            "locations": [physical_location(
+                problem.file_path.as_deref().unwrap_or_default(),
+                problem.line,
+                problem.snippet.as_ref(),
+            )]
+        });
+        if let Some(code_flows) = code_flows(problem) {
+            result["codeFlows"] = code_flows;
+        }
+        if let Some(suppression) = &problem.suppression {
+            result["suppressions"] = json!([{
+                "kind": "external",
+                "justification": suppression.reason
+            }]);
+        }
+        if let Some(state) = problem.baseline_state {
+            result["baselineState"] = json!(match state {
+                BaselineState::New => "new",
+                BaselineState::Unchanged => "unchanged",
+                BaselineState::Fixed => "absent",
+            });
+        }
+        results.push(result);
+    }
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "CodePecker",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules
+                }
+            },
+            "results": results
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        model::{test_problem, Suppression},
+        report::test_result,
+    };
+
+    #[test]
+    fn rules_are_shared_by_error_code() {
+        let result = test_result(vec![
+            test_problem(json!({ "severityLevel": 1 })),
+            test_problem(json!({ "errorCode": "SQLI", "severityLevel": 3 })),
+            test_problem(json!({ "line": 9, "severityLevel": 1 })),
+            test_problem(json!({ "errorCode": "LOG" })),
+        ]);
+        let sarif = build(&result);
+        let run = &sarif["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        let ids: Vec<&str> = rules.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["NPE", "SQLI", "LOG"]);
+        assert_eq!(rules[0]["properties"]["security-severity"], "9.5");
+
+        let results = run["results"].as_array().unwrap();
+        let indexes: Vec<&Value> = results.iter().map(|r| &r["ruleIndex"]).collect();
+        assert_eq!(indexes, [0, 1, 0, 2]);
+        let levels: Vec<&Value> = results.iter().map(|r| &r["level"]).collect();
+        assert_eq!(levels, ["error", "warning", "error", "note"]);
+    }
+
+    #[test]
+    fn rule_help_from_solution() {
+        let result = test_result(vec![test_problem(json!({
+            "solution": {
+                "wiki_description": "空指针",
+                "wiki_detail": "使用前检查",
+                "wiki_example": "if s != null"
+            }
+        }))]);
+        let sarif = build(&result);
+        let rule = &sarif["runs"][0]["tool"]["driver"]["rules"][0];
+        assert_eq!(rule["shortDescription"]["text"], "空指针");
+        assert_eq!(rule["fullDescription"]["text"], "使用前检查");
+        assert_eq!(rule["help"]["text"], "空指针\n\n使用前检查\n\nif s != null");
+        assert_eq!(
+            rule["help"]["markdown"],
+            "**空指针**\n\n使用前检查\n\n```\nif s != null\n```"
+        );
+        assert_eq!(sarif["runs"][0]["results"][0]["message"]["text"], "空指针");
+    }
+
+    #[test]
+    fn locations_snippets_and_code_flows() {
+        let result = test_result(vec![test_problem(json!({
+            "filePath": "src\\A.java",
+            "line": 0,
+            "snippet": { "startLine": 1, "endLine": 2, "code": "String s = null;\ns.length();" },
+            "traceBlock": [
+                { "file": "src\\A.java", "line": 1, "message": "s = null" },
+                { "message": "没有文件的步骤" }
+            ]
+        }))]);
+        let sarif = build(&result);
+        let result = &sarif["runs"][0]["results"][0];
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/A.java");
+        assert_eq!(location["region"]["startLine"], 1);
+        assert_eq!(location["region"]["snippet"]["text"], "String s = null;");
+        assert_eq!(location["contextRegion"]["endLine"], 2);
+
+        let steps = result["codeFlows"][0]["threadFlows"][0]["locations"]
+            .as_array()
+            .unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0]["location"]["message"]["text"], "s = null");
+    }
+
+    #[test]
+    fn suppressions_and_baseline_state() {
+        let mut suppressed = test_problem(json!({}));
+        suppressed.suppression = Some(Suppression {
+            reason: "误报".to_owned(),
+            expires: None,
+        });
+        suppressed.baseline_state = Some(BaselineState::Unchanged);
+        let mut plain = test_problem(json!({}));
+        plain.baseline_state = Some(BaselineState::New);
+        let sarif = build(&test_result(vec![suppressed, plain]));
+        let results = &sarif["runs"][0]["results"];
+        assert_eq!(
+            results[0]["suppressions"],
+            json!([{ "kind": "external", "justification": "误报" }])
+        );
+        assert_eq!(results[0]["baselineState"], "unchanged");
+        assert!(results[1].get("suppressions").is_none());
+        assert_eq!(results[1]["baselineState"], "new");
+        assert!(results[1].get("codeFlows").is_none());
+    }
+}
@@ -0,0 +1,333 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// 缺陷或组件的级别, 按严重程度排序, 服务端返回的未知级别为 Unknown 且低于 info
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum Severity {
+    Unknown,
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// 已知的级别, 从高到低
+    pub(crate) const KNOWN: [Severity; 5] = [
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Low,
+        Severity::Info,
+    ];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Severity::Unknown => "unknown",
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Severity::KNOWN
+            .into_iter()
+            .chain([Severity::Unknown])
+            .find(|severity| severity.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("未知的级别 {s}, 可选值: critical,high,medium,low,info,unknown"))
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 服务端 severityLevel 与级别的对应关系, 默认为 1=critical,2=high,3=medium,4=low,5=info
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SeverityMap(BTreeMap<i64, Severity>);
+
+impl Default for SeverityMap {
+    fn default() -> Self {
+        Self((1..).zip(Severity::KNOWN).collect())
+    }
+}
+
+impl SeverityMap {
+    /// 没有 severityLevel 或没有对应关系时为 Unknown
+    pub(crate) fn severity(&self, severity_level: Option<i64>) -> Severity {
+        severity_level
+            .and_then(|level| self.0.get(&level).copied())
+            .unwrap_or(Severity::Unknown)
+    }
+}
+
+impl FromStr for SeverityMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut map = BTreeMap::new();
+        for item in s.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+            let (level, severity) = item
+                .split_once('=')
+                .ok_or_else(|| format!("级别对应关系 {item} 的格式应为 severityLevel=级别"))?;
+            let level = level
+                .trim()
+                .parse()
+                .map_err(|_| format!("级别对应关系 {item} 的 severityLevel 必须是整数"))?;
+            map.insert(level, severity.parse()?);
+        }
+        if map.is_empty() {
+            return Err("级别对应关系不能为空".to_owned());
+        }
+        Ok(Self(map))
+    }
+}
+
+impl fmt::Display for SeverityMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let items: Vec<String> = self
+            .0
+            .iter()
+            .map(|(level, severity)| format!("{level}={severity}"))
+            .collect();
+        write!(f, "{}", items.join(","))
+    }
+}
+
+impl Serialize for SeverityMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// 配置文件中与命令行参数的格式相同, eg. severity_map = "1=critical,2=high,3=medium,4=low,5=info"
+impl<'de> Deserialize<'de> for SeverityMap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// 级别筛选: high 表示 high 及以上, =medium 只包括 medium, low..=high 为范围(包括两端)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SeverityFilter {
+    AtLeast(Severity),
+    Exact(Severity),
+    Range(Severity, Severity),
+}
+
+impl Default for SeverityFilter {
+    fn default() -> Self {
+        SeverityFilter::AtLeast(Severity::Info)
+    }
+}
+
+impl SeverityFilter {
+    /// 筛选条件的中文描述, eg. high 及以上级别, medium 级别, low 至 high 级别
+    pub(crate) fn description(self) -> String {
+        match self {
+            SeverityFilter::AtLeast(Severity::Unknown) => "全部级别".to_owned(),
+            SeverityFilter::AtLeast(min) => format!("{min} 及以上级别"),
+            SeverityFilter::Exact(exact) => format!("{exact} 级别"),
+            SeverityFilter::Range(min, max) if min == max => format!("{min} 级别"),
+            SeverityFilter::Range(min, max) => format!("{min} 至 {max} 级别"),
+        }
+    }
+
+    /// 未知级别只在筛选条件包括 unknown 时匹配
+    pub(crate) fn matches(self, severity: Severity) -> bool {
+        match self {
+            SeverityFilter::AtLeast(min) => severity >= min,
+            SeverityFilter::Exact(exact) => severity == exact,
+            SeverityFilter::Range(min, max) => (min..=max).contains(&severity),
+        }
+    }
+}
+
+impl FromStr for SeverityFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(exact) = s.strip_prefix('=') {
+            return Ok(SeverityFilter::Exact(exact.parse()?));
+        }
+        let Some((min, max)) = s.split_once("..") else {
+            return Ok(SeverityFilter::AtLeast(s.parse()?));
+        };
+        let max = match max.strip_prefix('=') {
+            Some(max) => max,
+            None if max.trim().is_empty() => max,
+            None => return Err(format!("级别范围 {s} 的格式应为 low..=high, 范围包括两端")),
+        };
+        let min = if min.trim().is_empty() {
+            Severity::Unknown
+        } else {
+            min.parse()?
+        };
+        let max = if max.trim().is_empty() {
+            Severity::Critical
+        } else {
+            max.parse()?
+        };
+        if min > max {
+            return Err(format!("级别范围 {s} 的下限高于上限"));
+        }
+        Ok(SeverityFilter::Range(min, max))
+    }
+}
+
+impl fmt::Display for SeverityFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeverityFilter::AtLeast(min) => write!(f, "{min}"),
+            SeverityFilter::Exact(exact) => write!(f, "={exact}"),
+            SeverityFilter::Range(min, max) => write!(f, "{min}..={max}"),
+        }
+    }
+}
+
+impl Serialize for SeverityFilter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// 配置文件中与命令行参数的格式相同, eg. severity = "low..=high"
+impl<'de> Deserialize<'de> for SeverityFilter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_severity_filter() {
+        assert_eq!("high".parse(), Ok(SeverityFilter::AtLeast(Severity::High)));
+        assert_eq!(
+            " =Medium ".parse(),
+            Ok(SeverityFilter::Exact(Severity::Medium))
+        );
+        assert_eq!(
+            "low..=high".parse(),
+            Ok(SeverityFilter::Range(Severity::Low, Severity::High))
+        );
+        assert_eq!(
+            "unknown".parse(),
+            Ok(SeverityFilter::AtLeast(Severity::Unknown))
+        );
+    }
+
+    #[test]
+    fn parse_open_severity_range() {
+        assert_eq!(
+            "..=low".parse(),
+            Ok(SeverityFilter::Range(Severity::Unknown, Severity::Low))
+        );
+        assert_eq!(
+            "medium..".parse(),
+            Ok(SeverityFilter::Range(Severity::Medium, Severity::Critical))
+        );
+        assert_eq!(
+            "..".parse(),
+            Ok(SeverityFilter::Range(Severity::Unknown, Severity::Critical))
+        );
+        assert_eq!(
+            "high..=high".parse(),
+            Ok(SeverityFilter::Range(Severity::High, Severity::High))
+        );
+    }
+
+    #[test]
+    fn reject_invalid_severity_filter() {
+        for s in [
+            "",
+            "  ",
+            "=",
+            "urgent",
+            "=urgent",
+            "low..high",
+            "high..=low",
+            "low..=urgent",
+        ] {
+            assert!(s.parse::<SeverityFilter>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn severity_filter_display_round_trips() {
+        for s in ["high", "=medium", "low..=high", "unknown..=critical"] {
+            let filter: SeverityFilter = s.parse().unwrap();
+            assert_eq!(filter.to_string(), s);
+        }
+    }
+
+    #[test]
+    fn severity_filter_matches_unknown_only_when_included() {
+        let info: SeverityFilter = "info".parse().unwrap();
+        assert!(info.matches(Severity::Info));
+        assert!(!info.matches(Severity::Unknown));
+        let unknown: SeverityFilter = "unknown".parse().unwrap();
+        assert!(unknown.matches(Severity::Unknown));
+        assert!(unknown.matches(Severity::Critical));
+        let range: SeverityFilter = "low..=high".parse().unwrap();
+        assert!(!range.matches(Severity::Critical));
+        assert!(range.matches(Severity::Low));
+    }
+
+    #[test]
+    fn severity_filter_description() {
+        let description = |s: &str| s.parse::<SeverityFilter>().unwrap().description();
+        assert_eq!(description("high"), "high 及以上级别");
+        assert_eq!(description("=medium"), "medium 级别");
+        assert_eq!(description("low..=high"), "low 至 high 级别");
+        assert_eq!(description("high..=high"), "high 级别");
+        assert_eq!(description("unknown"), "全部级别");
+    }
+
+    #[test]
+    fn parse_severity_map() {
+        let map: SeverityMap = "0=critical, 1=high,,9=info".parse().unwrap();
+        assert_eq!(map.severity(Some(0)), Severity::Critical);
+        assert_eq!(map.severity(Some(1)), Severity::High);
+        assert_eq!(map.severity(Some(9)), Severity::Info);
+        assert_eq!(map.severity(Some(2)), Severity::Unknown);
+        assert_eq!(map.severity(None), Severity::Unknown);
+        assert_eq!(map.to_string(), "0=critical,1=high,9=info");
+        assert_eq!(
+            "1=critical,2=high,3=medium,4=low,5=info".parse(),
+            Ok(SeverityMap::default())
+        );
+    }
+
+    #[test]
+    fn reject_invalid_severity_map() {
+        for s in ["", " , ", "1", "a=high", "1.5=high", "1=urgent", "=high"] {
+            assert!(s.parse::<SeverityMap>().is_err(), "{s:?}");
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::{fs, path::Path};
+
+use chrono::{Local, NaiveDate};
+use globset::{GlobBuilder, GlobMatcher};
+use serde::Deserialize;
+
+use crate::{
+    error::CodepeckerError,
+    model::{Problem, Suppression},
+};
+
+/// 默认的忽略文件, 位于当前目录
+pub(crate) const DEFAULT_IGNORE_FILE: &str = ".codepecker-ignore.toml";
+
+// 忽略文件的格式:
+//
+// [[suppress]]
+// error_code = "NULL_POINTER"
+// path = "src/generated/**"
+// reason = "生成的代码, 不做处理"
+// expires = "2025-12-31"
+#[derive(Deserialize)]
+struct IgnoreFile {
+    #[serde(default)]
+    suppress: Vec<SuppressEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SuppressEntry {
+    error_code: Option<String>,
+    path: Option<String>,
+    fingerprint: Option<String>,
+    reason: String,
+    expires: Option<toml::Value>,
+}
+
+// 一条生效的忽略规则, 给出的条件需全部满足
+struct Rule {
+    error_code: Option<String>,
+    path: Option<GlobMatcher>,
+    fingerprint: Option<String>,
+    suppression: Suppression,
+}
+
+impl Rule {
+    fn matches(&self, problem: &Problem) -> bool {
+        self.error_code
+            .as_ref()
+            .is_none_or(|code| *code == problem.error_code)
+            && self.path.as_ref().is_none_or(|glob| {
+                problem
+                    .file_path
+                    .as_ref()
+                    .is_some_and(|path| glob.is_match(path.replace('\\', "/")))
+            })
+            && self
+                .fingerprint
+                .as_ref()
+                .is_none_or(|fingerprint| problem.fingerprint.as_ref() == Some(fingerprint))
+    }
+}
+
+/// 忽略文件中生效的规则
+pub(crate) struct Suppressions {
+    rules: Vec<Rule>,
+}
+
+// 过期时间支持 TOML 日期(2025-12-31)或字符串("2025-12-31")
+fn parse_expires(value: &toml::Value) -> Option<NaiveDate> {
+    let text = match value {
+        toml::Value::Datetime(datetime) => datetime.date?.to_string(),
+        toml::Value::String(text) => text.clone(),
+        _ => return None,
+    };
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok()
+}
+
+impl Suppressions {
+    // 读取忽略文件, 过期的规则给出警告且不生效
+    pub(crate) fn load(path: &Path) -> Result<Self, CodepeckerError> {
+        let invalid = |reason: String| {
+            CodepeckerError::InvalidConfig(format!("忽略文件{}不合法: {reason}", path.display()))
+        };
+        let content = fs::read_to_string(path)?;
+        let file: IgnoreFile = toml::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+        let today = Local::now().date_naive();
+        let mut rules = Vec::new();
+        for (index, entry) in file.suppress.into_iter().enumerate() {
+            let number = index + 1;
+            if entry.reason.trim().is_empty() {
+                return Err(invalid(format!("第{number}条规则缺少忽略原因reason")));
+            }
+            if entry.error_code.is_none() && entry.path.is_none() && entry.fingerprint.is_none() {
+                return Err(invalid(format!(
+                    "第{number}条规则至少需要error_code、path、fingerprint之一"
+                )));
+            }
+            let expires = match &entry.expires {
+                Some(value) => Some(parse_expires(value).ok_or_else(|| {
+                    invalid(format!("第{number}条规则的过期时间应为 YYYY-MM-DD 格式"))
+                })?),
+                None => None,
+            };
+            if let Some(expires) = expires {
+                if expires < today {
+                    log::warn!(
+                        "忽略文件第{number}条规则已于{expires}过期, 不再生效: {}",
+                        entry.reason
+                    );
+                    continue;
+                }
+            }
+            let path = match &entry.path {
+                Some(pattern) => Some(
+                    GlobBuilder::new(pattern)
+                        .literal_separator(true)
+                        .build()
+                        .map_err(|e| invalid(format!("第{number}条规则的path不合法: {e}")))?
+                        .compile_matcher(),
+                ),
+                None => None,
+            };
+            rules.push(Rule {
+                error_code: entry.error_code,
+                path,
+                fingerprint: entry.fingerprint,
+                suppression: Suppression {
+                    reason: entry.reason,
+                    expires: expires.map(|date| date.to_string()),
+                },
+            });
+        }
+        log::info!(
+            "从忽略文件{}读取{}条生效的规则",
+            path.display(),
+            rules.len()
+        );
+        Ok(Self { rules })
+    }
+
+    // 标记被忽略的缺陷, 被忽略的缺陷仍然输出
+    pub(crate) fn apply(&self, problems: &mut [Problem]) {
+        let mut count = 0;
+        for problem in problems {
+            if let Some(rule) = self.rules.iter().find(|rule| rule.matches(problem)) {
+                problem.suppression = Some(rule.suppression.clone());
+                count += 1;
+            }
+        }
+        log::info!("忽略文件匹配{count}个缺陷");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::test_problem;
+
+    fn load(content: &str) -> Result<Suppressions, CodepeckerError> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        Suppressions::load(file.path())
+    }
+
+    fn reasons(suppressions: &Suppressions, problems: &mut [Problem]) -> Vec<Option<String>> {
+        suppressions.apply(problems);
+        problems
+            .iter()
+            .map(|p| p.suppression.as_ref().map(|s| s.reason.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn rule_conditions_must_all_match() {
+        let suppressions = load(
+            r#"
+[[suppress]]
+error_code = "NPE"
+path = "src/generated/*.java"
+reason = "生成的代码"
+
+[[suppress]]
+fingerprint = "abc"
+reason = "误报"
+"#,
+        )
+        .unwrap();
+        let mut fingerprinted = test_problem(json!({ "errorCode": "SQLI" }));
+        fingerprinted.fingerprint = Some("abc".to_owned());
+        let mut problems = [
+            test_problem(json!({ "filePath": "src\\generated\\A.java" })),
+            // * 不匹配路径分隔符
+            test_problem(json!({ "filePath": "src/generated/x/A.java" })),
+            test_problem(json!({ "errorCode": "SQLI", "filePath": "src/generated/A.java" })),
+            fingerprinted,
+        ];
+        assert_eq!(
+            reasons(&suppressions, &mut problems),
+            [
+                Some("生成的代码".to_owned()),
+                None,
+                None,
+                Some("误报".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn expired_rules_are_skipped() {
+        let suppressions = load(
+            r#"
+[[suppress]]
+error_code = "NPE"
+reason = "已过期"
+expires = 2000-01-01
+
+[[suppress]]
+error_code = "NPE"
+reason = "未过期"
+expires = "2999-12-31"
+"#,
+        )
+        .unwrap();
+        assert_eq!(suppressions.rules.len(), 1);
+        let mut problems = [test_problem(json!({}))];
+        suppressions.apply(&mut problems);
+        let suppression = problems[0].suppression.as_ref().unwrap();
+        assert_eq!(suppression.reason, "未过期");
+        assert_eq!(suppression.expires.as_deref(), Some("2999-12-31"));
+    }
+
+    #[test]
+    fn reject_invalid_rules() {
+        for content in [
+            "[[suppress]]\nerror_code = \"NPE\"\nreason = \" \"",
+            "[[suppress]]\nreason = \"没有条件\"",
+            "[[suppress]]\nerror_code = \"NPE\"\nreason = \"r\"\nexpires = \"2025/01/01\"",
+            "[[suppress]]\npath = \"src/[\"\nreason = \"r\"",
+            "[[suppress]]\nerror_code = \"NPE\"\nreason = \"r\"\nunknown = 1",
+        ] {
+            assert!(
+                matches!(load(content), Err(CodepeckerError::InvalidConfig(_))),
+                "{content}"
+            );
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+
+use crate::model::WikiResponse;
+
+// 文件名中只保留字母、数字及 - _ . 字符
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// 缓存文件名: 可读的名称加原始名称的哈希, 避免 A/B 与 A_B 这样清理后相同的名称使用同一个文件
+fn file_name(name: &str) -> String {
+    let digest = Sha256::digest(name.as_bytes());
+    let hash: String = digest[..8].iter().map(|b| format!("{b:02x}")).collect();
+    format!("{}-{hash}", sanitize(name))
+}
+
+/// 规则wiki的本地缓存, 按服务端地址、语言及errorCode存储在 ~/.cache/codepecker/wiki/ 下
+pub(crate) struct WikiCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl WikiCache {
+    // 没有用户缓存目录时返回 None
+    pub(crate) fn new(server: &Url, language: &str, ttl: Duration) -> Option<Self> {
+        let digest = Sha256::digest(server.as_str().as_bytes());
+        let hash: String = digest[..4].iter().map(|b| format!("{b:02x}")).collect();
+        let host = match server.port_or_known_default() {
+            Some(port) => format!("{}_{port}", server.host_str().unwrap_or("localhost")),
+            None => server.host_str().unwrap_or("localhost").to_owned(),
+        };
+        let dir = dirs::cache_dir()?
+            .join("codepecker")
+            .join("wiki")
+            .join(sanitize(&format!("{host}-{hash}")))
+            .join(file_name(language));
+        Some(Self { dir, ttl })
+    }
+
+    fn path(&self, error_code: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", file_name(error_code)))
+    }
+
+    // 读取缓存的wiki, allow_expired 为 false 时忽略超过有效期的缓存
+    pub(crate) fn get(&self, error_code: &str, allow_expired: bool) -> Option<WikiResponse> {
+        let path = self.path(error_code);
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if !allow_expired && age > self.ttl {
+            log::debug!("{error_code}的wiki缓存已过期");
+            return None;
+        }
+        let content = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(wiki) => {
+                log::debug!("从缓存{}读取{error_code}的wiki", path.display());
+                Some(wiki)
+            }
+            Err(e) => {
+                log::warn!("wiki缓存{}不合法, 将重新获取: {e}", path.display());
+                None
+            }
+        }
+    }
+
+    // 写入缓存, 失败时只给出警告
+    pub(crate) fn put(&self, error_code: &str, wiki: &WikiResponse) {
+        let path = self.path(error_code);
+        let result = fs::create_dir_all(&self.dir).and_then(|_| {
+            let content = serde_json::to_vec(wiki)?;
+            fs::write(&path, content)
+        });
+        if let Err(e) = result {
+            log::warn!("写入wiki缓存{}失败: {e}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> WikiCache {
+        WikiCache {
+            dir: PathBuf::from("wiki"),
+            ttl: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn distinct_error_codes_use_distinct_files() {
+        let cache = cache();
+        assert_ne!(cache.path("A/B"), cache.path("A_B"));
+        assert_ne!(cache.path("A:B"), cache.path("A?B"));
+        assert_eq!(cache.path("NULL_POINTER"), cache.path("NULL_POINTER"));
+    }
+
+    #[test]
+    fn file_names_stay_in_cache_dir() {
+        let path = cache().path("../../etc/passwd");
+        assert_eq!(path.parent(), Some(PathBuf::from("wiki").as_path()));
+        assert!(path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with(".._.._etc_passwd-"));
+    }
+}